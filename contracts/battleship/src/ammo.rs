@@ -0,0 +1,115 @@
+// Ammo-limited games, GameMode::Ammo(shots). Each player gets `shots` shots
+// for the whole game, counted by shots_fired_pN, so a cancelled shot comes
+// back and a conceded one doesn't. A player whose last shot has been
+// answered without sinking the fleet loses.
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::{
+    finish_game, load_game, other_player, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, GameMode, GamePhase, GameState,
+};
+
+// None outside ammo mode
+pub(crate) fn shots_remaining(state: &GameState, player: &Address) -> Option<u32> {
+    let GameMode::Ammo(shots) = state.mode else {
+        return None;
+    };
+    let fired = if *player == state.player1 {
+        state.shots_fired_p1
+    } else {
+        state.shots_fired_p2
+    };
+    Some(shots.saturating_sub(fired))
+}
+
+// Called once an exchange completes without a winner
+pub(crate) fn check_ammo(env: &Env, state: &mut GameState, shooter: &Address) {
+    if state.phase == GamePhase::Playing && shots_remaining(state, shooter) == Some(0) {
+        finish_game(env, state, other_player(state, shooter));
+    }
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // (player1's shots left, player2's), or None outside ammo mode
+    pub fn get_shots_remaining(env: Env) -> Option<(u32, u32)> {
+        let state = load_game(&env).expect("No game");
+        Some((
+            shots_remaining(&state, &state.player1)?,
+            shots_remaining(&state, &state.player2)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{proof, setup, start_game_with};
+    use crate::{BattleshipContractClient, GameMode, GameOptions, GamePhase};
+    use soroban_sdk::{Address, Env};
+
+    fn start_ammo(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        let options = GameOptions { mode: GameMode::Ammo(2), ..Default::default() };
+        start_game_with(env, client, p1, p2, &options);
+    }
+
+    #[test]
+    fn test_running_out_of_ammo_loses() {
+        let (env, p1, p2, client) = setup();
+        start_ammo(&env, &client, &p1, &p2);
+        assert_eq!(client.get_shots_remaining(), Some((2, 2)));
+
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_shots_remaining(), Some((1, 2)));
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+
+        client.fire_shot(&p1, &1, &0);
+        client.submit_response(&p2, &1, &0, &proof(&env, false));
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p2);
+        assert_eq!(client.get_shots_remaining(), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_cannot_fire_without_ammo() {
+        let (env, p1, p2, client) = setup();
+        start_ammo(&env, &client, &p1, &p2);
+
+        // A hit keeps the game going past p1's last shot
+        for x in 0..2u32 {
+            client.fire_shot(&p1, &x, &0);
+            client.submit_response(&p2, &x, &0, &proof(&env, true));
+        }
+        assert_eq!(client.get_state().turn, p1);
+        assert_eq!(client.get_shots_remaining(), Some((0, 2)));
+        assert!(!client.can_fire(&p1, &2, &0));
+        assert!(client.try_fire_shot(&p1, &2, &0).is_err());
+    }
+
+    #[test]
+    fn test_cancelled_shot_is_refunded() {
+        let (env, p1, p2, client) = setup();
+        start_ammo(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &0, &0);
+        client.cancel_shot(&p1);
+        assert_eq!(client.get_shots_remaining(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_no_ammo_outside_ammo_mode() {
+        let (_env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        assert_eq!(client.get_shots_remaining(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid ammo")]
+    fn test_zero_ammo_rejected() {
+        let (_env, p1, _p2, client) = setup();
+        client.create_game(&p1, &GameOptions { mode: GameMode::Ammo(0), ..Default::default() });
+    }
+}
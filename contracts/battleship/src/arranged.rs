@@ -0,0 +1,76 @@
+// Arranged matches: two players who agreed on a game off-chain join and
+// commit their boards in one transaction, signed by both, going straight to
+// Playing with player1 to fire first.
+
+use soroban_sdk::{contractimpl, Address, BytesN, Env, Vec};
+
+use crate::{
+    assert_initialized, assert_not_reentrant, commit, has_game, join, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, GameOptions,
+};
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn join_both(env: Env, p1: Address, p2: Address, hash1: BytesN<32>, hash2: BytesN<32>) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        assert!(p1 != p2, "Players must differ");
+        p1.require_auth();
+        p2.require_auth();
+        assert!(!has_game(&env), "Game already exists");
+
+        join(&env, p1.clone(), GameOptions::default());
+        join(&env, p2.clone(), GameOptions::default());
+        commit(&env, p1, Vec::from_array(&env, [hash1]));
+        commit(&env, p2, Vec::from_array(&env, [hash2]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, setup};
+    use crate::GamePhase;
+    use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+    use soroban_sdk::{vec, IntoVal};
+
+    #[test]
+    fn test_join_both() {
+        let (env, p1, p2, client) = setup();
+        client.join_both(&p1, &p2, &hash(&env, 1), &hash(&env, 2));
+
+        let auths = env.auths();
+        assert_eq!(auths.len(), 2);
+        assert_eq!((&auths[0].0, &auths[1].0), (&p1, &p2));
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!((&state.player1, &state.player2), (&p1, &p2));
+        assert_eq!(state.board_hash_p1, vec![&env, hash(&env, 1)]);
+        assert_eq!(state.board_hash_p2, vec![&env, hash(&env, 2)]);
+        assert_eq!(state.turn, p1);
+    }
+
+    #[test]
+    fn test_join_both_needs_both_auths() {
+        let (env, p1, p2, client) = setup();
+        let (hash1, hash2) = (hash(&env, 1), hash(&env, 2));
+        env.mock_auths(&[MockAuth {
+            address: &p1,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "join_both",
+                args: (&p1, &p2, &hash1, &hash2).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        assert!(client.try_join_both(&p1, &p2, &hash1, &hash2).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Players must differ")]
+    fn test_join_both_same_player() {
+        let (env, p1, _p2, client) = setup();
+        client.join_both(&p1, &p1, &hash(&env, 1), &hash(&env, 2));
+    }
+}
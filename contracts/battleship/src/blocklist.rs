@@ -0,0 +1,71 @@
+// Operator blocklist. The admin from Config can bar known cheaters; a blocked
+// address can't join or create a game, through any entry point, or enter the
+// matchmaking queue. Games already under way are left alone.
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::{
+    assert_initialized, assert_not_reentrant, load_config, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, DataKey,
+};
+
+pub(crate) fn is_blocked(env: &Env, player: &Address) -> bool {
+    env.storage().persistent().has(&DataKey::Blocked(player.clone()))
+}
+
+fn require_admin(env: &Env) {
+    load_config(env).expect("Not initialized").admin.require_auth();
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn add_to_blocklist(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        require_admin(&env);
+        env.storage().persistent().set(&DataKey::Blocked(player), &true);
+    }
+
+    pub fn remove_from_blocklist(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        require_admin(&env);
+        env.storage().persistent().remove(&DataKey::Blocked(player));
+    }
+
+    pub fn is_blocked(env: Env, player: Address) -> bool {
+        is_blocked(&env, &player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{setup_uninitialized, test_config};
+    use crate::GamePhase;
+
+    #[test]
+    fn test_blocked_address_cannot_join() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        client.join_game(&p1);
+
+        client.add_to_blocklist(&p2);
+        assert!(client.is_blocked(&p2));
+        assert!(client.try_join_game(&p2).is_err());
+        assert!(client.try_enqueue(&p2, &0).is_err());
+
+        client.remove_from_blocklist(&p2);
+        client.join_game(&p2);
+        assert_eq!(client.get_state().phase, GamePhase::Commit);
+    }
+
+    #[test]
+    #[should_panic(expected = "ADDRESS_BLOCKED")]
+    fn test_blocked_join_reverts() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        client.add_to_blocklist(&p1);
+        client.join_game(&p1);
+    }
+}
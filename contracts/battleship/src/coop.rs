@@ -0,0 +1,176 @@
+// Co-op mode, GameMode::CoOp(turns). Both players defend one shared board —
+// they must commit the same hash — against a scripted attacker. Every shot
+// is derived from the session id and the move number, so the sequence is
+// fixed when the game is created but unknown to the players beforehand; it
+// never repeats a cell. The players take turns answering, starting with
+// player1, through the usual submit_response proof. Surviving `turns` shots
+// wins; losing the fleet first loses.
+//
+// The attacker's shots go in fired_mask_p2 and the hits on the shared board
+// in hits_on_p1 / hit_mask_p1, as if player2 were shooting at player1, so
+// reveal_board checks player1's revealed board the usual way. No winner is
+// recorded, stats and the hub are left alone, and game_over carries whether
+// the team survived.
+
+use soroban_sdk::{Address, Bytes, Env};
+
+use crate::events::publish;
+use crate::{
+    bump, cell_bit, hits_to_win, other_player, pass_turn, stamp, track_finished, GameMode,
+    GamePhase, GameState, BOARD_SIZE,
+};
+
+pub(crate) fn is_coop(state: &GameState) -> bool {
+    matches!(state.mode, GameMode::CoOp(_))
+}
+
+// The n-th unfired cell, n picked by sha256(session_id ‖ move_number)
+fn next_target(env: &Env, state: &GameState) -> (u32, u32) {
+    let mut seed = Bytes::new(env);
+    seed.extend_from_array(&state.session_id.to_be_bytes());
+    seed.extend_from_array(&state.move_number.to_be_bytes());
+    let digest = env.crypto().sha256(&seed).to_array();
+    let roll = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+
+    let cells = BOARD_SIZE * BOARD_SIZE;
+    let open = cells - state.fired_mask_p2.count_ones();
+    let mut n = roll % open;
+    for cell in 0..cells {
+        if state.fired_mask_p2 & (1 << cell) != 0 {
+            continue;
+        }
+        if n == 0 {
+            return (cell % BOARD_SIZE, cell / BOARD_SIZE);
+        }
+        n -= 1;
+    }
+    unreachable!()
+}
+
+// Queue the attacker's next shot for whoever holds the turn
+pub(crate) fn attack(env: &Env, state: &mut GameState) {
+    let (x, y) = next_target(env, state);
+    state.fired_mask_p2 |= cell_bit(x, y);
+    state.pending_shot_x = x;
+    state.pending_shot_y = y;
+    state.pending_shooter = env.current_contract_address();
+    bump(&mut state.shots_fired_p2);
+    publish(env, state, "shot_fired", (env.current_contract_address(), x, y));
+}
+
+// A verified answer to the attacker's shot; the pending shot is cleared and
+// move_number already counts it
+pub(crate) fn respond(
+    env: &Env,
+    state: &mut GameState,
+    defender: &Address,
+    x: u32,
+    y: u32,
+    is_hit: bool,
+) {
+    let GameMode::CoOp(turns) = state.mode else {
+        unreachable!()
+    };
+    if is_hit {
+        bump(&mut state.hits_on_p1);
+        state.hit_mask_p1 |= cell_bit(x, y);
+        if state.hits_on_p1 >= hits_to_win(env, state, &state.player1.clone()) {
+            end(env, state, false);
+            return;
+        }
+    }
+    if state.move_number >= turns {
+        end(env, state, true);
+        return;
+    }
+    pass_turn(env, state, other_player(state, defender));
+    attack(env, state);
+}
+
+pub(crate) fn end(env: &Env, state: &mut GameState, survived: bool) {
+    state.phase = GamePhase::Finished;
+    stamp(env, |t| &mut t.finished_at);
+    track_finished(env, state);
+    publish(env, state, "game_over", survived);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, proof, setup};
+    use crate::{BattleshipContractClient, GameMode, GameOptions, GamePhase};
+    use soroban_sdk::{Address, Env};
+
+    fn start_coop(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address, turns: u32) {
+        let options = GameOptions { mode: GameMode::CoOp(turns), ..Default::default() };
+        client.create_game(p1, &options);
+        client.join_game(p2);
+        client.commit_board(p1, &hash(env, 1));
+        client.commit_board(p2, &hash(env, 1));
+    }
+
+    // Answers the attacker's pending shot, returning the cell it hit
+    fn answer(env: &Env, client: &BattleshipContractClient, is_hit: bool) -> (u32, u32) {
+        let state = client.get_state();
+        let (x, y) = (state.pending_shot_x, state.pending_shot_y);
+        client.submit_response(&state.turn, &x, &y, &proof(env, is_hit));
+        (x, y)
+    }
+
+    #[test]
+    fn test_coop_survive() {
+        let (env, p1, p2, client) = setup();
+        start_coop(&env, &client, &p1, &p2, 3);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.pending_shooter, client.address);
+        assert!(client.try_fire_shot(&p1, &0, &0).is_err());
+
+        // The defenders alternate, and no cell is shot twice
+        assert_eq!(client.get_state().turn, p1);
+        let first = answer(&env, &client, true);
+        assert_eq!(client.get_state().turn, p2);
+        let second = answer(&env, &client, false);
+        assert_ne!(first, second);
+        assert_eq!(client.get_state().turn, p1);
+        answer(&env, &client, false);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.hits_on_p1, 1);
+        assert!(!state.has_winner);
+    }
+
+    #[test]
+    fn test_coop_pending_defender() {
+        let (env, p1, p2, client) = setup();
+        start_coop(&env, &client, &p1, &p2, 3);
+
+        assert_eq!(client.get_pending_defender(), Some(p1));
+        answer(&env, &client, false);
+        assert_eq!(client.get_pending_defender(), Some(p2));
+    }
+
+    #[test]
+    fn test_coop_fleet_lost() {
+        let (env, p1, p2, client) = setup();
+        start_coop(&env, &client, &p1, &p2, 10);
+
+        for _ in 0..3 {
+            answer(&env, &client, true);
+        }
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.move_number, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Co-op boards differ")]
+    fn test_coop_needs_shared_board() {
+        let (env, p1, p2, client) = setup();
+        client.create_game(&p1, &GameOptions { mode: GameMode::CoOp(3), ..Default::default() });
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+    }
+}
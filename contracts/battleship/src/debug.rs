@@ -0,0 +1,75 @@
+// Support tooling. get_debug_snapshot bundles everything needed to triage a
+// user report into one call; it only answers on deployments with
+// Config.dev_mode set.
+
+use soroban_sdk::{contractimpl, contracttype, Env};
+
+use crate::{
+    load_config, load_game, load_stats, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, GameState, PlayerStats,
+};
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct DebugSnapshot {
+    pub active_games_p1: u32,
+    pub active_games_p2: u32,
+    pub finished_games_p1: u32,
+    pub finished_games_p2: u32,
+    pub state: GameState,
+    pub stats_p1: PlayerStats,
+    pub stats_p2: PlayerStats,
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn get_debug_snapshot(env: Env) -> DebugSnapshot {
+        let dev_mode = load_config(&env).map(|config| config.dev_mode).unwrap_or(false);
+        assert!(dev_mode, "Dev mode only");
+
+        let state = load_game(&env).expect("No game");
+        let (p1, p2) = (state.player1.clone(), state.player2.clone());
+
+        DebugSnapshot {
+            active_games_p1: Self::get_active_games(env.clone(), p1.clone()).len(),
+            active_games_p2: Self::get_active_games(env.clone(), p2.clone()).len(),
+            finished_games_p1: Self::get_finished_games(env.clone(), p1.clone()).len(),
+            finished_games_p2: Self::get_finished_games(env.clone(), p2.clone()).len(),
+            stats_p1: load_stats(&env, &p1),
+            stats_p2: load_stats(&env, &p2),
+            state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{setup_uninitialized, test_config};
+    use crate::{Config, GamePhase};
+
+    #[test]
+    fn test_debug_snapshot() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { dev_mode: true, ..test_config(&env) });
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.surrender(&p2);
+
+        let snapshot = client.get_debug_snapshot();
+        assert_eq!(snapshot.state.phase, GamePhase::Finished);
+        assert_eq!(snapshot.stats_p1.games_won, 1);
+        assert_eq!(snapshot.stats_p2.forfeits, 1);
+        assert_eq!((snapshot.active_games_p1, snapshot.finished_games_p1), (0, 1));
+        assert_eq!((snapshot.active_games_p2, snapshot.finished_games_p2), (0, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Dev mode only")]
+    fn test_debug_snapshot_in_production() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        client.join_game(&p1);
+        client.get_debug_snapshot();
+    }
+}
@@ -0,0 +1,242 @@
+// Disputes over a finished game's result, for cases the automated logic
+// can't settle. Within Config.dispute_window seconds of the game finishing
+// either player may raise one; while it's open the payout is frozen, and the
+// admin resolves it by confirming the winner, reassigning the win, or calling
+// a draw that refunds both stakes. With a window set, claim_winnings also
+// waits for the window to pass unless a dispute has already been resolved,
+// so a pot can't leave before the loser has had their chance. The dispute
+// lives under DataKey::Dispute(session_id).
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::events::publish;
+use crate::recent_games::amend_result;
+use crate::timeline::load_timeline;
+use crate::{
+    assert_initialized, assert_not_reentrant, load_config, load_game, other_player, reassign_win,
+    save_game, shift_stats, stake_token, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, DataKey, GamePhase, GameState,
+};
+
+/// Open until the admin rules: Upheld keeps the result, Reassigned hands the
+/// win to the other player, Drawn voids it and refunds both stakes.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DisputeStatus {
+    Open,
+    Upheld,
+    Reassigned,
+    Drawn,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dispute {
+    pub raised_at: u64,
+    pub raised_by: Address,
+    pub status: DisputeStatus,
+}
+
+fn load_dispute(env: &Env, session_id: u32) -> Option<Dispute> {
+    env.storage().persistent().get::<DataKey, Dispute>(&DataKey::Dispute(session_id))
+}
+
+fn window_ends_at(env: &Env) -> u64 {
+    let window = load_config(env).map(|config| config.dispute_window).unwrap_or(0);
+    load_timeline(env).finished_at.saturating_add(window)
+}
+
+pub(crate) fn dispute_open(env: &Env, state: &GameState) -> bool {
+    load_dispute(env, state.session_id).is_some_and(|dispute| dispute.status == DisputeStatus::Open)
+}
+
+// Called by claim_winnings
+pub(crate) fn assert_payout_allowed(env: &Env, state: &GameState) {
+    match load_dispute(env, state.session_id) {
+        Some(dispute) => assert!(dispute.status != DisputeStatus::Open, "Payout frozen"),
+        None if window_set(env) => {
+            assert!(env.ledger().timestamp() > window_ends_at(env), "Dispute window open")
+        }
+        None => {}
+    }
+}
+
+fn window_set(env: &Env) -> bool {
+    load_config(env).is_some_and(|config| config.dispute_window > 0)
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn raise_dispute(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::Finished && state.has_winner, "Game not finished");
+        assert!(player == state.player1 || player == state.player2, "Not a player");
+        assert!(window_set(&env), "Disputes disabled");
+        assert!(env.ledger().timestamp() <= window_ends_at(&env), "Dispute window closed");
+        assert!(load_dispute(&env, state.session_id).is_none(), "Already disputed");
+
+        let dispute = Dispute {
+            raised_at: env.ledger().timestamp(),
+            raised_by: player.clone(),
+            status: DisputeStatus::Open,
+        };
+        env.storage().persistent().set(&DataKey::Dispute(state.session_id), &dispute);
+        publish(&env, &state, "dispute_raised", player);
+    }
+
+    /// Settle the open dispute: `winner` confirms or reassigns the win, None
+    /// draws the game and refunds both stakes
+    pub fn resolve_dispute(env: Env, admin: Address, winner: Option<Address>) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        let config = load_config(&env).expect("Not initialized");
+        assert!(admin == config.admin, "Not admin");
+        admin.require_auth();
+
+        let mut state = load_game(&env).expect("No game");
+        let mut dispute = load_dispute(&env, state.session_id).expect("No dispute");
+        assert!(dispute.status == DisputeStatus::Open, "Dispute resolved");
+
+        let old_winner = state.winner.clone();
+        let old_loser = other_player(&state, &old_winner);
+        dispute.status = match winner {
+            Some(winner) if winner == old_winner => DisputeStatus::Upheld,
+            Some(winner) => {
+                assert!(winner == old_loser, "Not a player");
+                reassign_win(&env, &mut state);
+                DisputeStatus::Reassigned
+            }
+            None => {
+                shift_stats(&env, &old_winner, &old_loser, true);
+                state.has_winner = false;
+                amend_result(&env, state.session_id, None);
+                DisputeStatus::Drawn
+            }
+        };
+
+        let refund = dispute.status == DisputeStatus::Drawn && state.pot > 0;
+        if refund {
+            state.pot = 0;
+        }
+        env.storage().persistent().set(&DataKey::Dispute(state.session_id), &dispute);
+        save_game(&env, &state);
+
+        if refund {
+            let token = stake_token(&env);
+            let contract = env.current_contract_address();
+            token.transfer(&contract, &state.player1, &state.stake);
+            token.transfer(&contract, &state.player2, &state.stake);
+        }
+        publish(&env, &state, "dispute_resolved", dispute.status);
+    }
+
+    pub fn get_dispute(env: Env, game_id: u32) -> Option<Dispute> {
+        load_dispute(&env, game_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DisputeStatus;
+    use crate::tests::{
+        setup, setup_staking_with_config, setup_uninitialized, start_game_with, test_config,
+    };
+    use crate::{BattleshipContractClient, Config, GameOptions};
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::{token, Address, Env};
+
+    // p2 surrenders to p1 at t = 1_000 under a ten-minute dispute window
+    fn finish(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address, stake: i128) {
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        start_game_with(env, client, p1, p2, &GameOptions { stake, ..Default::default() });
+        client.surrender(p2);
+    }
+
+    #[test]
+    fn test_dispute_reassigns_winner() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { dispute_window: 600, ..test_config(&env) });
+        finish(&env, &client, &p1, &p2, 0);
+        let admin = client.get_config().unwrap().admin;
+
+        client.raise_dispute(&p2);
+        let session_id = client.get_state().session_id;
+        assert_eq!(client.get_dispute(&session_id).unwrap().status, DisputeStatus::Open);
+        assert!(client.try_raise_dispute(&p1).is_err());
+        assert!(client.try_reset_game(&p1).is_err());
+
+        client.resolve_dispute(&admin, &Some(p2.clone()));
+        let state = client.get_state();
+        assert_eq!(state.winner, p2);
+        assert_eq!(client.get_dispute(&session_id).unwrap().status, DisputeStatus::Reassigned);
+        assert_eq!(client.get_last_game_result(&p1).unwrap().winner, p2);
+
+        let (stats1, stats2) = (client.get_player_stats(&p1), client.get_player_stats(&p2));
+        let (stats1, stats2) = (stats1.unwrap(), stats2.unwrap());
+        assert_eq!((stats1.games_won, stats1.games_lost), (0, 1));
+        assert_eq!((stats2.games_won, stats2.games_lost), (1, 0));
+        assert!(stats2.rating > stats1.rating);
+
+        // Settled for good
+        assert!(client.try_resolve_dispute(&admin, &None).is_err());
+        client.reset_game(&p1);
+    }
+
+    #[test]
+    fn test_dispute_drawn_with_refunds() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let config = Config { dispute_window: 600, ..test_config(&env) };
+        let token = setup_staking_with_config(&env, &client, &[&p1, &p2], config);
+        finish(&env, &client, &p1, &p2, 100);
+        let admin = client.get_config().unwrap().admin;
+
+        // The winner can't be paid while the loser may still dispute
+        assert!(client.try_claim_winnings(&p1).is_err());
+        env.ledger().with_mut(|l| l.timestamp = 1_300);
+        client.raise_dispute(&p2);
+        assert!(client.try_claim_winnings(&p1).is_err());
+
+        client.resolve_dispute(&admin, &None);
+        let state = client.get_state();
+        assert!(!state.has_winner);
+        assert_eq!(state.pot, 0);
+        let token = token::Client::new(&env, &token);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1000, 1000));
+        assert_eq!(client.get_last_game_result(&p1), None);
+        assert_eq!(client.get_player_stats(&p1).unwrap().games_won, 0);
+    }
+
+    #[test]
+    fn test_dispute_window_closes() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { dispute_window: 600, ..test_config(&env) });
+        finish(&env, &client, &p1, &p2, 0);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_601);
+        assert!(client.try_raise_dispute(&p2).is_err());
+        assert!(client.get_dispute(&client.get_state().session_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Disputes disabled")]
+    fn test_disputes_need_a_window() {
+        let (env, p1, p2, client) = setup();
+        finish(&env, &client, &p1, &p2, 0);
+        client.raise_dispute(&p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not admin")]
+    fn test_only_admin_resolves() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { dispute_window: 600, ..test_config(&env) });
+        finish(&env, &client, &p1, &p2, 0);
+        client.raise_dispute(&p2);
+        client.resolve_dispute(&p2, &Some(p2.clone()));
+    }
+}
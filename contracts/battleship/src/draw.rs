@@ -0,0 +1,205 @@
+// Draws. With Config.max_moves set, a game that reaches that many completed
+// exchanges without a winner stops: a free game is drawn on the spot, while a
+// wagered one waits for both players to agree with propose_draw so nobody
+// loses a pot over a rule they didn't accept. Nobody can move at the limit,
+// so it can't end in a forfeit either: an inactivity claim there draws the
+// game instead, and once MOVE_LIMIT_GRACE has passed since the last move one
+// player's propose_draw is enough. propose_draw works at any point of play,
+// not just at the limit. A draw refunds both stakes and counts as a game
+// played for each side, neither won nor lost.
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::events::publish;
+use crate::{
+    assert_initialized, assert_not_reentrant, bump, load_config, load_game, load_stats,
+    other_player, save_game, stake_token, stamp, track_finished, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, DataKey, GamePhase, GameState,
+};
+
+const MOVE_LIMIT_GRACE: u64 = 86_400; // 1 day
+
+pub(crate) fn move_limit_reached(env: &Env, state: &GameState) -> bool {
+    let max_moves = load_config(env).map(|config| config.max_moves).unwrap_or(0);
+    max_moves > 0 && state.move_number >= max_moves
+}
+
+fn move_limit_expired(env: &Env, state: &GameState) -> bool {
+    let idle = env.ledger().timestamp().saturating_sub(state.last_move_at);
+    move_limit_reached(env, state) && idle >= MOVE_LIMIT_GRACE
+}
+
+// Called once an exchange completes without a winner
+pub(crate) fn check_move_limit(env: &Env, state: &mut GameState) {
+    if !move_limit_reached(env, state) {
+        return;
+    }
+    if state.pot == 0 {
+        end_in_draw(env, state);
+    } else {
+        publish(env, state, "move_limit_reached", state.move_number);
+    }
+}
+
+pub(crate) fn end_in_draw(env: &Env, state: &mut GameState) {
+    state.phase = GamePhase::Finished;
+    stamp(env, |t| &mut t.finished_at);
+    env.storage().instance().remove(&DataKey::DrawOffer);
+
+    for player in [&state.player1, &state.player2] {
+        let mut stats = load_stats(env, player);
+        bump(&mut stats.games_played);
+        env.storage().persistent().set(&DataKey::PlayerStats(player.clone()), &stats);
+    }
+    track_finished(env, state);
+
+    let refund = state.pot > 0;
+    state.pot = 0;
+    save_game(env, state);
+
+    if refund {
+        let token = stake_token(env);
+        let contract = env.current_contract_address();
+        token.transfer(&contract, &state.player1, &state.stake);
+        token.transfer(&contract, &state.player2, &state.stake);
+    }
+    publish(env, state, "game_drawn", state.move_number);
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // Offers a draw, or accepts the opponent's standing offer. Past the move
+    // limit's grace period it draws the game outright.
+    pub fn propose_draw(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let mut state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::Playing, "Not playing");
+        assert!(player == state.player1 || player == state.player2, "Not a player");
+
+        let offer = env.storage().instance().get::<DataKey, Address>(&DataKey::DrawOffer);
+        if offer == Some(other_player(&state, &player)) || move_limit_expired(&env, &state) {
+            end_in_draw(&env, &mut state);
+        } else {
+            env.storage().instance().set(&DataKey::DrawOffer, &player);
+            publish(&env, &state, "draw_proposed", player);
+        }
+    }
+
+    pub fn get_draw_offer(env: Env) -> Option<Address> {
+        env.storage().instance().get::<DataKey, Address>(&DataKey::DrawOffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MOVE_LIMIT_GRACE;
+    use crate::tests::{hash, proof, setup_staking_with_config, setup_uninitialized, test_config};
+    use crate::{BattleshipContractClient, Config, GameOptions, GamePhase};
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::{token, Address, Env};
+
+    // Two misses each way
+    fn play_misses(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.commit_board(p1, &hash(env, 1));
+        client.commit_board(p2, &hash(env, 2));
+        for x in 0..2u32 {
+            client.fire_shot(p1, &x, &4);
+            client.submit_response(p2, &x, &4, &proof(env, false));
+        }
+    }
+
+    #[test]
+    fn test_free_game_draws_at_move_limit() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { max_moves: 2, ..test_config(&env) });
+        client.join_game(&p1);
+        client.join_game(&p2);
+        play_misses(&env, &client, &p1, &p2);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(!state.has_winner);
+        let stats = client.get_player_stats(&p1).unwrap();
+        assert_eq!((stats.games_played, stats.games_won, stats.games_lost), (1, 0, 0));
+    }
+
+    #[test]
+    fn test_wagered_game_needs_draw_agreement() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let config = Config { max_moves: 2, ..test_config(&env) };
+        let token = setup_staking_with_config(&env, &client, &[&p1, &p2], config);
+        let token = token::Client::new(&env, &token);
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        play_misses(&env, &client, &p1, &p2);
+
+        // Play stops at the limit, but the pot stays until both agree
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+        assert!(!client.can_fire(&p1, &2, &4));
+        assert!(client.try_fire_shot(&p1, &2, &4).is_err());
+
+        client.propose_draw(&p1);
+        assert_eq!(client.get_draw_offer(), Some(p1.clone()));
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+        client.propose_draw(&p2);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.pot, 0);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+        assert_eq!(client.get_draw_offer(), None);
+    }
+
+    // A wagered game stuck at a two-move limit
+    fn stuck<'a>(
+        env: &'a Env,
+        client: &BattleshipContractClient,
+        p1: &Address,
+        p2: &Address,
+    ) -> token::Client<'a> {
+        let config = Config { absence_window: 3_600, max_moves: 2, ..test_config(env) };
+        let token = setup_staking_with_config(env, client, &[p1, p2], config);
+        client.create_game(p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(p2);
+        play_misses(env, client, p1, p2);
+        token::Client::new(env, &token)
+    }
+
+    #[test]
+    fn test_claim_at_move_limit_draws() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = stuck(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp += 3_600);
+        client.claim_inactivity(&p2);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(!state.has_winner);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1000, 1000));
+    }
+
+    #[test]
+    fn test_move_limit_times_out_into_draw() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = stuck(&env, &client, &p1, &p2);
+
+        // p2 never agrees
+        client.propose_draw(&p1);
+        env.ledger().with_mut(|l| l.timestamp += MOVE_LIMIT_GRACE - 1);
+        client.propose_draw(&p1);
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+
+        env.ledger().with_mut(|l| l.timestamp += 1);
+        client.propose_draw(&p1);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(!state.has_winner);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1000, 1000));
+    }
+}
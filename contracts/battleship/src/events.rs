@@ -0,0 +1,161 @@
+// Contract events. Topics are always (name, session_id) so subscribers can
+// pick one game out of everything the contract emits. Deployments that turn
+// off Config.verbose_events only get game_started and game_over. With
+// Config.private_shots, shot_fired and shot_cancelled carry only the shooter,
+// so spectators learn a cell's coordinates from shot_result, once answered.
+
+use soroban_sdk::{Env, IntoVal, Symbol, Val};
+
+use crate::{load_config, GameState};
+
+const LIFECYCLE_EVENTS: [&str; 2] = ["game_started", "game_over"];
+
+pub(crate) fn private_shots(env: &Env) -> bool {
+    load_config(env).is_some_and(|config| config.private_shots)
+}
+
+pub(crate) fn publish(env: &Env, state: &GameState, name: &str, data: impl IntoVal<Env, Val>) {
+    publish_for(env, state.session_id, name, data);
+}
+
+pub(crate) fn publish_for(env: &Env, session_id: u32, name: &str, data: impl IntoVal<Env, Val>) {
+    let verbose = load_config(env).map(|config| config.verbose_events).unwrap_or(true);
+    if !verbose && !LIFECYCLE_EVENTS.contains(&name) {
+        return;
+    }
+    env.events().publish((Symbol::new(env, name), session_id), data);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        proof, setup, setup_uninitialized, start_game, start_game_with, test_config,
+    };
+    use crate::{Config, GameOptions};
+    use soroban_sdk::{testutils::Events, vec, Address, Env, IntoVal, Symbol, Val, Vec};
+
+    #[test]
+    fn test_topics_carry_session_id() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        let session_id = client.get_state().session_id;
+
+        client.fire_shot(&p1, &2, &3);
+        let (contract, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(contract, client.address);
+        let expected: Vec<Val> = vec![
+            &env,
+            Symbol::new(&env, "shot_fired").into_val(&env),
+            session_id.into_val(&env),
+        ];
+        assert_eq!(topics, expected);
+        let data: (Address, u32, u32) = data.into_val(&env);
+        assert_eq!(data, (p1.clone(), 2, 3));
+
+        client.submit_response(&p2, &2, &3, &proof(&env, false));
+        for (_, topics, _) in env.events().all().iter() {
+            assert_eq!(topics.len(), 2);
+            let id: u32 = topics.get_unchecked(1).into_val(&env);
+            assert_eq!(id, session_id);
+        }
+    }
+
+    // The newest turn_changed event's turn holder
+    fn last_turn_change(env: &Env) -> Option<Address> {
+        let name = Symbol::new(env, "turn_changed");
+        let mut holder = None;
+        for (_, topics, data) in env.events().all().iter() {
+            let topic: Symbol = topics.get_unchecked(0).into_val(env);
+            if topic == name {
+                holder = Some(data.into_val(env));
+            }
+        }
+        holder
+    }
+
+    #[test]
+    fn test_turn_changed() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        assert_eq!(last_turn_change(&env), None);
+
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(last_turn_change(&env), Some(p2.clone()));
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        assert_eq!(last_turn_change(&env), Some(p1.clone()));
+        client.fire_shot(&p1, &1, &1);
+        assert_eq!(last_turn_change(&env), Some(p2.clone()));
+    }
+
+    // The data of the newest event called `name`
+    fn last_event(env: &Env, name: &str) -> Val {
+        let name = Symbol::new(env, name);
+        env.events()
+            .all()
+            .iter()
+            .filter(|(_, topics, _)| {
+                let topic: Symbol = topics.get_unchecked(0).into_val(env);
+                topic == name
+            })
+            .last()
+            .map(|(_, _, data)| data)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_private_shots() {
+        for private_shots in [false, true] {
+            let (env, p1, p2, client) = setup_uninitialized();
+            client.initialize(&Config { private_shots, ..test_config(&env) });
+            start_game(&env, &client, &p1, &p2);
+
+            client.fire_shot(&p1, &2, &3);
+            let fired = last_event(&env, "shot_fired");
+            client.cancel_shot(&p1);
+            let cancelled = last_event(&env, "shot_cancelled");
+            if private_shots {
+                let fired: Address = fired.into_val(&env);
+                let cancelled: Address = cancelled.into_val(&env);
+                assert_eq!((fired, cancelled), (p1.clone(), p1.clone()));
+            } else {
+                let fired: (Address, u32, u32) = fired.into_val(&env);
+                let cancelled: (Address, u32, u32) = cancelled.into_val(&env);
+                assert_eq!(fired, (p1.clone(), 2, 3));
+                assert_eq!(cancelled, (p1.clone(), 2, 3));
+            }
+
+            // The answer gives the cell away either way
+            client.fire_shot(&p1, &2, &3);
+            client.submit_response(&p2, &2, &3, &proof(&env, false));
+            let result: (Address, u32, u32, bool) = last_event(&env, "shot_result").into_val(&env);
+            assert_eq!(result, (p2.clone(), 2, 3, false));
+        }
+    }
+
+    // Plays a one-ship game and returns the names of the events emitted
+    fn play_and_collect(verbose_events: bool) -> (Env, Vec<Symbol>) {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { verbose_events, ..test_config(&env) });
+        let options = GameOptions { ships_to_sink_p2: 1, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+
+        let mut names = Vec::new(&env);
+        for (_, topics, _) in env.events().all().iter() {
+            names.push_back(topics.get_unchecked(0).into_val(&env));
+        }
+        (env, names)
+    }
+
+    #[test]
+    fn test_quiet_events() {
+        let (env, verbose) = play_and_collect(true);
+        let (_, quiet) = play_and_collect(false);
+        assert!(quiet.len() < verbose.len());
+        assert!(verbose.contains(Symbol::new(&env, "shot_result")));
+        for name in quiet.iter() {
+            assert!(name == Symbol::new(&env, "game_started") || name == Symbol::new(&env, "game_over"));
+        }
+    }
+}
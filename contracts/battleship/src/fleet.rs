@@ -0,0 +1,311 @@
+// Fleet-of-boards games, GameMode::Fleet(boards). Each player commits one
+// board hash per grid, so board_hash holds `boards` single-field
+// commitments, and shots go to a chosen grid with fire_at_board (fire_shot
+// aims at grid 0). Every grid holds a full fleet of ships_to_sink ships, and
+// a player wins by sinking the fleets on all of the opponent's grids.
+// Fleet games are unwagered: reveal_board only covers a single grid.
+//
+// GameState's fired and hit masks only describe a single grid, so a fleet
+// game leaves them at 0 and keeps per-grid masks and hit counts under
+// DataKey::FleetBoards(session_id, owner) instead; hits_on_pN still count
+// hits across every grid. The grid the pending shot aims at is kept under
+// DataKey::PendingBoard(session_id), and the defender's proof is checked
+// against that grid's hash and hit mask alone. A shooter runs out of
+// targets only once every cell of every grid has been fired at.
+
+use soroban_sdk::{contractimpl, contracttype, Address, BytesN, Env, Vec};
+
+use crate::{
+    assert_initialized, assert_not_reentrant, cell_bit, fire, fired_cells, load_game, other_player,
+    validate_shot, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey,
+    GameMode, GameState, ALL_CELLS, BOARD_SIZE,
+};
+
+/// Per-grid shot state for one player's boards, indexed by grid: `fired`
+/// is the cells the opponent has shot at, `hit_masks` the ones that hit,
+/// and `hits` how many hits each grid has taken.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FleetBoards {
+    pub fired: Vec<u32>,
+    pub hit_masks: Vec<u32>,
+    pub hits: Vec<u32>,
+}
+
+// Number of grids per player; None outside fleet mode
+pub(crate) fn fleet_size(state: &GameState) -> Option<u32> {
+    match state.mode {
+        GameMode::Fleet(boards) => Some(boards),
+        _ => None,
+    }
+}
+
+fn load_boards(env: &Env, state: &GameState, owner: &Address) -> FleetBoards {
+    let key = DataKey::FleetBoards(state.session_id, owner.clone());
+    env.storage().persistent().get::<DataKey, FleetBoards>(&key).unwrap_or_else(|| {
+        let boards = fleet_size(state).unwrap_or(0);
+        let mut zeros = Vec::new(env);
+        for _ in 0..boards {
+            zeros.push_back(0);
+        }
+        FleetBoards { fired: zeros.clone(), hit_masks: zeros.clone(), hits: zeros }
+    })
+}
+
+fn save_boards(env: &Env, state: &GameState, owner: &Address, boards: &FleetBoards) {
+    let key = DataKey::FleetBoards(state.session_id, owner.clone());
+    env.storage().persistent().set(&key, boards);
+}
+
+// Whether `board` names one of the grids: any of them in a fleet game, only
+// board 0 otherwise
+pub(crate) fn check_board_index(state: &GameState, board: u32) -> Result<(), &'static str> {
+    match fleet_size(state) {
+        Some(boards) if board >= boards => Err("No such board"),
+        None if board != 0 => Err("Not a fleet game"),
+        _ => Ok(()),
+    }
+}
+
+// The cells of the opponent's grid `board` that `shooter` has fired at
+pub(crate) fn fired_at(env: &Env, state: &GameState, shooter: &Address, board: u32) -> u32 {
+    let defender = other_player(state, shooter);
+    load_boards(env, state, &defender).fired.get(board).unwrap_or(0)
+}
+
+// Whether `shooter` has fired at every cell of every one of the opponent's grids
+pub(crate) fn all_fired(env: &Env, state: &GameState, shooter: &Address) -> bool {
+    let defender = other_player(state, shooter);
+    load_boards(env, state, &defender).fired.iter().all(|fired| fired == ALL_CELLS)
+}
+
+fn pending_board(env: &Env, state: &GameState) -> u32 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::PendingBoard(state.session_id))
+        .unwrap_or(0)
+}
+
+// Marks `cell` of the opponent's grid `board` as fired at and makes it the
+// pending shot's grid. validate_shot has checked the grid and cell.
+pub(crate) fn aim(env: &Env, state: &GameState, shooter: &Address, board: u32, cell: u32) {
+    let defender = other_player(state, shooter);
+    let mut fleet = load_boards(env, state, &defender);
+    fleet.fired.set(board, fleet.fired.get_unchecked(board) | cell);
+    save_boards(env, state, &defender, &fleet);
+    env.storage().persistent().set(&DataKey::PendingBoard(state.session_id), &board);
+}
+
+// Takes the pending shot's cell back off its grid
+pub(crate) fn unaim(env: &Env, state: &GameState, shooter: &Address, cell: u32) {
+    let board = pending_board(env, state);
+    let defender = other_player(state, shooter);
+    let mut fleet = load_boards(env, state, &defender);
+    fleet.fired.set(board, fleet.fired.get_unchecked(board) & !cell);
+    save_boards(env, state, &defender, &fleet);
+}
+
+// The commitment and hit mask of the grid the pending shot aims at — what
+// the defender's proof is checked against
+pub(crate) fn target(env: &Env, state: &GameState, defender: &Address) -> (Vec<BytesN<32>>, u32) {
+    let board = pending_board(env, state);
+    let committed = if *defender == state.player1 {
+        &state.board_hash_p1
+    } else {
+        &state.board_hash_p2
+    };
+    let board_hash = Vec::from_array(env, [committed.get_unchecked(board)]);
+    (board_hash, load_boards(env, state, defender).hit_masks.get_unchecked(board))
+}
+
+// Records a hit on the pending shot's grid. Returns whether every one of the
+// defender's grids has now taken `per_board` hits.
+pub(crate) fn record_hit(
+    env: &Env,
+    state: &GameState,
+    defender: &Address,
+    cell: u32,
+    per_board: u32,
+) -> bool {
+    let board = pending_board(env, state);
+    let mut fleet = load_boards(env, state, defender);
+    fleet.hit_masks.set(board, fleet.hit_masks.get_unchecked(board) | cell);
+    fleet.hits.set(board, fleet.hits.get_unchecked(board) + 1);
+    save_boards(env, state, defender, &fleet);
+    fleet.hits.iter().all(|hits| hits >= per_board)
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// fire_shot at one of the opponent's grids in a fleet game
+    pub fn fire_at_board(env: Env, shooter: Address, board: u32, x: u32, y: u32) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        shooter.require_auth();
+        fire(&env, shooter, board, x, y);
+    }
+
+    /// can_fire for fire_at_board
+    pub fn can_fire_at_board(env: Env, shooter: Address, board: u32, x: u32, y: u32) -> bool {
+        let state = load_game(&env).expect("No game");
+        validate_shot(&env, &state, &shooter, board, x, y).is_ok()
+    }
+
+    /// get_available_targets for one of the opponent's grids
+    pub fn get_available_targets_at_board(env: Env, attacker: Address, board: u32) -> u32 {
+        let state = load_game(&env).expect("No game");
+        if let Err(reason) = check_board_index(&state, board) {
+            panic!("{}", reason);
+        }
+        ALL_CELLS & !fired_cells(&env, &state, &attacker, board)
+    }
+
+    /// was_fired for one of the opponent's grids
+    pub fn was_fired_at_board(env: Env, attacker: Address, board: u32, x: u32, y: u32) -> bool {
+        assert!(x < BOARD_SIZE, "x out of range");
+        assert!(y < BOARD_SIZE, "y out of range");
+
+        let state = load_game(&env).expect("No game");
+        if let Err(reason) = check_board_index(&state, board) {
+            panic!("{}", reason);
+        }
+        fired_cells(&env, &state, &attacker, board) & cell_bit(x, y) != 0
+    }
+
+    // Shot state of `owner`'s grids, or None outside fleet mode
+    pub fn get_fleet_boards(env: Env, owner: Address) -> Option<FleetBoards> {
+        let state = load_game(&env).expect("No game");
+        fleet_size(&state)?;
+        Some(load_boards(&env, &state, &owner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FleetBoards;
+    use crate::tests::{hash, proof, setup_staking, setup_uninitialized, test_config};
+    use crate::{Config, GameMode, GameOptions, GamePhase, OutOfTargets, ALL_CELLS, BOARD_SIZE};
+    use soroban_sdk::{Env, Vec};
+
+    // Each board hash [s; 32] stands for a grid whose one ship sits at x = s
+    mod grid_verifier {
+        use soroban_sdk::{contract, contractimpl, Bytes, Env};
+
+        #[contract]
+        pub struct GridVerifier;
+
+        #[contractimpl]
+        impl GridVerifier {
+            pub fn verify_proof(_env: Env, public_inputs: Bytes, _proof: Bytes) -> Option<bool> {
+                // One grid's hash, then x and y
+                assert_eq!(public_inputs.len(), 96);
+                Some(public_inputs.get(63) == public_inputs.get(0))
+            }
+        }
+    }
+
+    fn masks(env: &Env, values: [u32; 2]) -> Vec<u32> {
+        Vec::from_array(env, values)
+    }
+
+    #[test]
+    fn test_two_board_fleet() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(grid_verifier::GridVerifier, ());
+        client.initialize(&Config { verifier: Some(verifier), ..test_config(&env) });
+        let options = GameOptions {
+            mode: GameMode::Fleet(2),
+            ships_to_sink_p1: 1,
+            ships_to_sink_p2: 1,
+            ..Default::default()
+        };
+        client.create_game(&p1, &options);
+        client.join_game(&p2);
+
+        let one_board = Vec::from_array(&env, [hash(&env, 1)]);
+        assert!(client.try_commit_board_elements(&p1, &one_board).is_err());
+        client.commit_board_elements(&p1, &Vec::from_array(&env, [hash(&env, 2), hash(&env, 4)]));
+        client.commit_board_elements(&p2, &Vec::from_array(&env, [hash(&env, 1), hash(&env, 3)]));
+
+        // The same cell on each grid is its own shot, proven against that grid
+        client.fire_at_board(&p1, &0, &3, &0);
+        client.submit_response(&p2, &3, &0, &proof(&env, true));
+        client.fire_at_board(&p1, &1, &3, &0);
+        client.submit_response(&p2, &3, &0, &proof(&env, false));
+        assert_eq!(
+            client.get_fleet_boards(&p2),
+            Some(FleetBoards {
+                fired: masks(&env, [1 << 3, 1 << 3]),
+                hit_masks: masks(&env, [0, 1 << 3]),
+                hits: masks(&env, [0, 1]),
+            })
+        );
+        assert!(client.try_fire_at_board(&p1, &1, &3, &0).is_err());
+        assert!(client.try_fire_at_board(&p1, &2, &0, &0).is_err());
+
+        // The shot views see each grid on its own
+        assert!(!client.can_fire_at_board(&p1, &1, &3, &0));
+        assert!(!client.can_fire_at_board(&p1, &2, &0, &0));
+        assert!(client.can_fire_at_board(&p1, &1, &2, &0));
+        assert!(!client.can_fire(&p1, &3, &0));
+        assert!(client.was_fired(&p1, &3, &0));
+        assert!(!client.was_fired_at_board(&p1, &1, &2, &0));
+        assert_eq!(client.get_available_targets_at_board(&p1, &1), ALL_CELLS & !(1 << 3));
+        assert_eq!(client.get_available_targets(&p1), ALL_CELLS & !(1 << 3));
+
+        // One grid sunk isn't enough — the last hit on the other one wins
+        let state = client.get_state();
+        assert_eq!((state.phase, state.hits_on_p2, state.hit_mask_p2), (GamePhase::Playing, 1, 0));
+        client.fire_at_board(&p1, &0, &1, &0);
+        client.submit_response(&p2, &1, &0, &proof(&env, false));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+    }
+
+    #[test]
+    fn test_fleet_out_of_targets() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { out_of_targets: OutOfTargets::Draw, ..test_config(&env) });
+        client.create_game(&p1, &GameOptions { mode: GameMode::Fleet(2), ..Default::default() });
+        client.join_game(&p2);
+        client.commit_board_elements(&p1, &Vec::from_array(&env, [hash(&env, 1), hash(&env, 2)]));
+        client.commit_board_elements(&p2, &Vec::from_array(&env, [hash(&env, 3), hash(&env, 4)]));
+
+        // Every cell of grid 0 leaves grid 1 to shoot at
+        for board in 0..2 {
+            assert_eq!(client.get_state().phase, GamePhase::Playing);
+            for i in 0..BOARD_SIZE * BOARD_SIZE {
+                let (x, y) = (i % BOARD_SIZE, i / BOARD_SIZE);
+                client.fire_at_board(&p1, &board, &x, &y);
+                client.submit_response(&p2, &x, &y, &proof(&env, false));
+            }
+        }
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(!state.has_winner);
+    }
+
+    #[test]
+    fn test_fleet_size_checked() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        for boards in [0, 1, 9] {
+            let options = GameOptions { mode: GameMode::Fleet(boards), ..Default::default() };
+            assert!(client.try_create_game(&p1, &options).is_err());
+        }
+        // Only fleet games have per-grid state
+        client.join_game(&p1);
+        assert!(client.get_fleet_boards(&p1).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Fleet games are unwagered")]
+    fn test_fleet_games_unwagered() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        setup_staking(&env, &client, &[&p1]);
+        let options = GameOptions { mode: GameMode::Fleet(2), stake: 100, ..Default::default() };
+        client.create_game(&p1, &options);
+    }
+}
@@ -0,0 +1,363 @@
+// Game hub integration. When Config.hub is set and Config.enable_hub is on,
+// the hub is told when a match starts playing and how it ended. Standalone
+// deployments turn enable_hub off and never call out. Both calls leave the
+// contract, so they run under a lock that every state-mutating entrypoint
+// checks first. The host already rejects direct contract re-entry; the lock
+// keeps us safe regardless.
+//
+// start_game may answer with a session id of the hub's own. The game then
+// adopts it, moving everything keyed by the local id over, so both sides
+// name the match the same way from then on. Hubs that return nothing leave
+// the local id in place.
+
+use soroban_sdk::{contractclient, Address, BytesN, Env, IntoVal, TryFromVal, Val, Vec};
+
+use crate::events::publish;
+use crate::phase_index::reindex;
+use crate::player_games::track_renamed;
+use crate::{load_config, save_game, DataKey, FleetBoards, GameState, LobbyDeposit};
+
+#[allow(dead_code)]
+#[contractclient(name = "GameHubClient")]
+pub trait GameHub {
+    fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) -> Option<u32>;
+
+    fn end_game(env: Env, session_id: u32, player1_won: bool);
+}
+
+pub(crate) fn assert_not_reentrant(env: &Env) {
+    assert!(!env.storage().instance().has(&DataKey::HubLock), "REENTRANCY");
+}
+
+fn with_hub_lock(env: &Env, call: impl FnOnce(&GameHubClient)) {
+    let hub = load_config(env).and_then(|config| config.hub.filter(|_| config.enable_hub));
+    let Some(hub) = hub else {
+        return;
+    };
+    env.storage().instance().set(&DataKey::HubLock, &true);
+    call(&GameHubClient::new(env, &hub));
+    env.storage().instance().remove(&DataKey::HubLock);
+}
+
+fn rekey<V>(env: &Env, from: DataKey, to: DataKey)
+where
+    V: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    if let Some(value) = env.storage().persistent().get::<DataKey, V>(&from) {
+        env.storage().persistent().set(&to, &value);
+        env.storage().persistent().remove(&from);
+    }
+}
+
+// Renames the game to `session_id`, moving over everything written under the
+// old id before play started: lobby deposit, spectators, plain boards, the
+// bracket slot it was scheduled for and any per-game tallies. Results and
+// disputes only appear once the game finishes, so they're already under the
+// new id
+fn adopt_session_id(env: &Env, state: &mut GameState, session_id: u32) {
+    let old = state.session_id;
+    if session_id == old {
+        return;
+    }
+    let id_key = DataKey::GameId(session_id);
+    assert!(!env.storage().persistent().has(&id_key), "Game id collision");
+    env.storage().persistent().set(&id_key, &());
+
+    let stored = env.storage().persistent().get::<DataKey, GameState>(&DataKey::Game(old));
+    reindex(env, old, stored.map(|stored| stored.phase), None);
+    env.storage().persistent().remove(&DataKey::Game(old));
+    rekey::<LobbyDeposit>(env, DataKey::LobbyDeposit(old), DataKey::LobbyDeposit(session_id));
+    rekey::<Vec<Address>>(env, DataKey::Spectators(old), DataKey::Spectators(session_id));
+    rekey::<u32>(env, DataKey::PendingBoard(old), DataKey::PendingBoard(session_id));
+    rekey::<u32>(env, DataKey::ProofsVerified(old), DataKey::ProofsVerified(session_id));
+    rekey::<(u32, u32)>(env, DataKey::ShipsSunk(old), DataKey::ShipsSunk(session_id));
+    rekey::<(u32, u32)>(env, DataKey::Points(old), DataKey::Points(session_id));
+    rekey::<(u32, u32, u32)>(env, DataKey::BracketGame(old), DataKey::BracketGame(session_id));
+    for player in [state.player1.clone(), state.player2.clone()] {
+        let plain = |id| DataKey::PlainBoard(id, player.clone());
+        rekey::<u32>(env, plain(old), plain(session_id));
+        let fleet = |id| DataKey::FleetBoards(id, player.clone());
+        rekey::<FleetBoards>(env, fleet(old), fleet(session_id));
+    }
+    for move_index in 0..state.move_number {
+        let proof = |id| DataKey::MoveProof(id, move_index);
+        rekey::<BytesN<32>>(env, proof(old), proof(session_id));
+    }
+
+    state.session_id = session_id;
+    track_renamed(env, state, old);
+    save_game(env, state);
+    publish(env, state, "session_adopted", old);
+}
+
+pub(crate) fn call_hub_start(env: &Env, state: &mut GameState) {
+    let mut adopted = None;
+    with_hub_lock(env, |hub| {
+        adopted = hub.start_game(
+            &env.current_contract_address(),
+            &state.session_id,
+            &state.player1,
+            &state.player2,
+            &state.stake,
+            &state.stake,
+        );
+    });
+    if let Some(session_id) = adopted {
+        adopt_session_id(env, state, session_id);
+    }
+}
+
+pub(crate) fn call_hub_end(env: &Env, state: &GameState) {
+    with_hub_lock(env, |hub| {
+        hub.end_game(&state.session_id, &(state.winner == state.player1));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{hash, proof, setup, setup_uninitialized, start_game, test_config};
+    use crate::{BattleshipContractClient, Config, GameMode, GameOptions, GamePhase};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, vec, Address, Env};
+
+    // Records the calls it receives
+    mod mock_hub {
+        use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+        #[contract]
+        pub struct MockHub;
+
+        #[contractimpl]
+        impl MockHub {
+            pub fn start_game(
+                env: Env,
+                _game_id: Address,
+                session_id: u32,
+                _player1: Address,
+                _player2: Address,
+                _player1_points: i128,
+                _player2_points: i128,
+            ) {
+                env.storage().instance().set(&symbol_short!("started"), &session_id);
+            }
+
+            pub fn end_game(env: Env, _session_id: u32, player1_won: bool) {
+                env.storage().instance().set(&symbol_short!("p1_won"), &player1_won);
+            }
+        }
+    }
+
+    // Tries to sneak a surrender in on player2's behalf as soon as play starts
+    mod reentrant_hub {
+        use crate::BattleshipContractClient;
+        use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+        #[contract]
+        pub struct ReentrantHub;
+
+        #[contractimpl]
+        impl ReentrantHub {
+            pub fn start_game(
+                env: Env,
+                game_id: Address,
+                _session_id: u32,
+                _player1: Address,
+                player2: Address,
+                _player1_points: i128,
+                _player2_points: i128,
+            ) {
+                let game = BattleshipContractClient::new(&env, &game_id);
+                let reentered = game.try_surrender(&player2).is_ok();
+                env.storage().instance().set(&symbol_short!("reenter"), &reentered);
+            }
+
+            pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {}
+        }
+    }
+
+    // Hands out its own session ids, counting up from 9000
+    mod issuing_hub {
+        use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+        #[contract]
+        pub struct IssuingHub;
+
+        #[contractimpl]
+        impl IssuingHub {
+            pub fn start_game(
+                env: Env,
+                _game_id: Address,
+                _session_id: u32,
+                _player1: Address,
+                _player2: Address,
+                _player1_points: i128,
+                _player2_points: i128,
+            ) -> Option<u32> {
+                let key = symbol_short!("next");
+                let next = env.storage().instance().get::<_, u32>(&key).unwrap_or(9000);
+                env.storage().instance().set(&key, &(next + 1));
+                Some(next)
+            }
+
+            pub fn end_game(env: Env, session_id: u32, _player1_won: bool) {
+                env.storage().instance().set(&symbol_short!("ended"), &session_id);
+            }
+        }
+    }
+
+    // Fails any call, so a game only gets through if the hub is never invoked
+    mod failing_hub {
+        use soroban_sdk::{contract, contractimpl, Address, Env};
+
+        #[contract]
+        pub struct FailingHub;
+
+        #[contractimpl]
+        impl FailingHub {
+            pub fn start_game(
+                _env: Env,
+                _game_id: Address,
+                _session_id: u32,
+                _player1: Address,
+                _player2: Address,
+                _player1_points: i128,
+                _player2_points: i128,
+            ) {
+                panic!("hub called");
+            }
+
+            pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {
+                panic!("hub called");
+            }
+        }
+    }
+
+    fn init_with_hub(env: &Env, client: &BattleshipContractClient, hub: &Address) {
+        client.initialize(&Config { hub: Some(hub.clone()), ..test_config(env) });
+    }
+
+    #[test]
+    fn test_hub_notified_on_start_and_end() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hub = env.register(mock_hub::MockHub, ());
+        init_with_hub(&env, &client, &hub);
+        start_game(&env, &client, &p1, &p2);
+
+        let session_id = client.get_state().session_id;
+        env.as_contract(&hub, || {
+            let started: u32 = env.storage().instance().get(&symbol_short!("started")).unwrap();
+            assert_eq!(started, session_id);
+        });
+
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+        env.as_contract(&hub, || {
+            let p1_won: bool = env.storage().instance().get(&symbol_short!("p1_won")).unwrap();
+            assert!(p1_won);
+        });
+    }
+
+    #[test]
+    fn test_hub_session_id_adopted() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hub = env.register(issuing_hub::IssuingHub, ());
+        init_with_hub(&env, &client, &hub);
+        let spectator = Address::generate(&env);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let local = client.get_state().session_id;
+        client.add_spectator(&local, &spectator);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+
+        let state = client.get_state();
+        assert_eq!(state.session_id, 9000);
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert!(client.get_game(&local).is_none());
+        assert_eq!(client.get_game(&9000).unwrap().session_id, 9000);
+        assert_eq!(client.get_active_games(&p1), vec![&env, 9000]);
+        assert_eq!(client.get_active_games(&p2), vec![&env, 9000]);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Playing, &10), vec![&env, 9000]);
+        assert_eq!(client.get_spectators(&9000), vec![&env, spectator]);
+
+        client.surrender(&p2);
+        assert_eq!(client.get_finished_games(&p1), vec![&env, 9000]);
+        env.as_contract(&hub, || {
+            let ended: u32 = env.storage().instance().get(&symbol_short!("ended")).unwrap();
+            assert_eq!(ended, 9000);
+        });
+    }
+
+    #[test]
+    fn test_adopted_transparent_game_keeps_boards() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hub = env.register(issuing_hub::IssuingHub, ());
+        init_with_hub(&env, &client, &hub);
+        let options = GameOptions {
+            mode: GameMode::Transparent,
+            ships_to_sink_p1: 2,
+            ships_to_sink_p2: 2,
+            ..Default::default()
+        };
+        client.create_game(&p1, &options);
+        client.join_game(&p2);
+        client.commit_plain_board(&p1, &0b11);
+        client.commit_plain_board(&p2, &0b11);
+        assert_eq!(client.get_state().session_id, 9000);
+
+        // A "miss" on a ship cell is still answered from the board
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        assert_eq!(client.get_state().hits_on_p2, 1);
+    }
+
+    #[test]
+    fn test_hub_disabled() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hub = env.register(failing_hub::FailingHub, ());
+        client.initialize(&Config { enable_hub: false, hub: Some(hub), ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+        assert_eq!(client.get_state().winner, p1);
+    }
+
+    #[test]
+    fn test_hub_cannot_reenter() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hub = env.register(reentrant_hub::ReentrantHub, ());
+        init_with_hub(&env, &client, &hub);
+        start_game(&env, &client, &p1, &p2);
+
+        env.as_contract(&hub, || {
+            let reentered: bool = env.storage().instance().get(&symbol_short!("reenter")).unwrap();
+            assert!(!reentered);
+        });
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+    }
+
+    #[test]
+    #[should_panic(expected = "REENTRANCY")]
+    fn test_entrypoints_refuse_while_locked() {
+        let (env, p1, _p2, client) = setup();
+        env.as_contract(&client.address, || {
+            env.storage().instance().set(&DataKey::HubLock, &true);
+        });
+        client.join_game(&p1);
+    }
+}
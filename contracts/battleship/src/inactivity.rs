@@ -0,0 +1,200 @@
+// Claiming a game from an opponent who stopped playing. The player waiting
+// on the move wins by forfeit once the player on move has run out of time by
+// any of three measures: the per-turn deadline (Config.turn_timeout), an
+// empty Blitz clock, or Config.absence_window seconds since the last move.
+// claim_inactivity accepts any of them; claim_timeout and claim_time_win
+// each insist on their own. All three share the same checks and emit
+// "inactivity_claimed" with (claimant, inactive player, idle seconds). At the
+// move limit nobody can move, so a claim there draws the game instead.
+//
+// A game Paused on an unreachable verifier can't be claimed until it has
+// been paused for PAUSE_TIMEOUT; after that the outage is treated as the
+// defender's stalled turn and any of the three claims goes through.
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::events::publish;
+use crate::{
+    assert_initialized, assert_not_reentrant, end_in_draw, forfeit_game, is_coop, load_config,
+    load_game, move_limit_reached, save_game, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, GameMode, GamePhase, GameState,
+};
+
+type Expiry = fn(&Env, &GameState) -> Result<(), &'static str>;
+
+pub(crate) const PAUSE_TIMEOUT: u64 = 86_400; // 1 day
+
+fn idle_time(env: &Env, state: &GameState) -> u64 {
+    env.ledger().timestamp() - state.last_move_at
+}
+
+fn turn_timed_out(env: &Env, state: &GameState) -> Result<(), &'static str> {
+    if state.turn_deadline == 0 {
+        return Err("No turn deadline");
+    }
+    if env.ledger().timestamp() <= state.turn_deadline {
+        return Err("Deadline not reached");
+    }
+    Ok(())
+}
+
+fn clock_expired(env: &Env, state: &GameState) -> Result<(), &'static str> {
+    if state.mode != GameMode::Blitz {
+        return Err("Untimed game");
+    }
+    let remaining = if state.turn == state.player1 {
+        state.remaining_time_p1
+    } else {
+        state.remaining_time_p2
+    };
+    if idle_time(env, state) < remaining {
+        return Err("Clock not expired");
+    }
+    Ok(())
+}
+
+fn absent(env: &Env, state: &GameState) -> Result<(), &'static str> {
+    let window = load_config(env).map(|config| config.absence_window).unwrap_or(0);
+    if window == 0 {
+        return Err("No absence window");
+    }
+    if idle_time(env, state) < window {
+        return Err("Absence window not reached");
+    }
+    Ok(())
+}
+
+fn any_expiry(env: &Env, state: &GameState) -> Result<(), &'static str> {
+    let expired = [turn_timed_out, clock_expired, absent]
+        .iter()
+        .any(|expiry| expiry(env, state).is_ok());
+    if expired { Ok(()) } else { Err("Opponent still active") }
+}
+
+fn claim(env: &Env, claimant: Address, expiry: Expiry) {
+    assert_not_reentrant(env);
+    assert_initialized(env);
+    claimant.require_auth();
+
+    let mut state = load_game(env).expect("No game");
+
+    let paused_out = state.phase == GamePhase::Paused && idle_time(env, &state) >= PAUSE_TIMEOUT;
+    assert!(state.phase == GamePhase::Playing || paused_out, "Not playing");
+    assert!(!is_coop(&state), "No claims in co-op");
+    assert!(claimant == state.player1 || claimant == state.player2, "Not a player");
+    assert!(state.turn != claimant, "It's your turn");
+    let expired = if paused_out { Ok(()) } else { expiry(env, &state) };
+    if let Err(reason) = expired {
+        panic!("{}", reason);
+    }
+
+    let inactive = state.turn.clone();
+    let idle = idle_time(env, &state);
+    publish(env, &state, "inactivity_claimed", (claimant.clone(), inactive, idle));
+    if move_limit_reached(env, &state) {
+        end_in_draw(env, &mut state);
+        return;
+    }
+    forfeit_game(env, &mut state, claimant);
+    save_game(env, &state);
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // The waiting player wins if the opponent has run out of time in any way
+    pub fn claim_inactivity(env: Env, claimant: Address) {
+        claim(&env, claimant, any_expiry);
+    }
+
+    // Whoever is waiting on the opponent wins once the turn deadline has passed
+    pub fn claim_timeout(env: Env, claimant: Address) {
+        claim(&env, claimant, turn_timed_out);
+    }
+
+    // Blitz games: the player not on move wins once the opponent's clock is empty
+    pub fn claim_time_win(env: Env, claimant: Address) {
+        claim(&env, claimant, clock_expired);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{proof, setup, setup_uninitialized, start_game, test_config};
+    use crate::{BattleshipContractClient, Config, GamePhase};
+    use soroban_sdk::{testutils::Ledger, Address, Env};
+
+    // P1 fires, P2 answers at t = 100; P1 is then on move
+    fn start(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.initialize(&Config { absence_window: 3_600, ..test_config(env) });
+        start_game(env, client, p1, p2);
+        client.fire_shot(p1, &0, &0);
+        env.ledger().with_mut(|l| l.timestamp = 100);
+        client.submit_response(p2, &0, &0, &proof(env, false));
+    }
+
+    #[test]
+    fn test_absence_one_second_early() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 100 + 3_599);
+        assert!(client.try_claim_inactivity(&p2).is_err());
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+    }
+
+    #[test]
+    fn test_absence_exactly_at_window() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 100 + 3_600);
+        client.claim_inactivity(&p2);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p2);
+        assert_eq!(client.get_forfeit_count(&p1), 1);
+    }
+
+    #[test]
+    fn test_absence_one_second_late() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 100 + 3_601);
+        client.claim_inactivity(&p2);
+        assert_eq!(client.get_state().winner, p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "It's your turn")]
+    fn test_inactive_player_cannot_claim() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 100 + 3_600);
+        client.claim_inactivity(&p1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not playing")]
+    fn test_claim_needs_playing_phase() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { absence_window: 3_600, ..test_config(&env) });
+        client.join_game(&p1);
+        client.join_game(&p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 10_000);
+        client.claim_inactivity(&p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Opponent still active")]
+    fn test_claim_without_any_limit() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_000_000);
+        client.claim_inactivity(&p2);
+    }
+}
@@ -1,24 +1,297 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype,
-    Address, Bytes, BytesN, Env,
+    contract, contractimpl, contracttype, token, xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Vec,
 };
 
-const TOTAL_SHIPS: u32 = 3;
+mod ammo;
+mod arranged;
+mod blocklist;
+mod coop;
+mod debug;
+mod dispute;
+mod draw;
+mod events;
+mod fleet;
+mod hub;
+mod inactivity;
+mod lobby_deposit;
+mod matchmaking;
+mod phase_index;
+mod placement;
+mod player_games;
+mod recent_games;
+mod relay;
+mod rematch;
+mod reveal;
+mod sinks;
+mod spectators;
+mod substitute;
+mod timeline;
+mod tournament;
+mod transparent;
+mod verifier;
+
+use ammo::{check_ammo, shots_remaining};
+use blocklist::is_blocked;
+use coop::is_coop;
+use dispute::dispute_open;
+use draw::{check_move_limit, end_in_draw, move_limit_reached};
+use events::{private_shots, publish};
+use fleet::{aim, all_fired, check_board_index, fired_at, fleet_size, record_hit, unaim};
+use hub::{assert_not_reentrant, call_hub_end, call_hub_start};
+use lobby_deposit::{refund_deposit, take_deposit};
+use phase_index::reindex;
+use player_games::{
+    track_abandoned, track_finished, track_joined, track_opponents, track_replaced,
+};
+use recent_games::{amend_result, push_recent};
+use rematch::cancel_rematch;
+use reveal::{assert_reveals_checkable, open_reveal_window, settle_pot};
+use sinks::{points_scored, record_sink, ships_sunk};
+use timeline::{load_timeline, stamp, stamp_playing_sequence};
+use transparent::plain_board;
+use verifier::{record_proof_hash, verify_zk_proof};
+
+pub use debug::DebugSnapshot;
+pub use dispute::{Dispute, DisputeStatus};
+pub use fleet::FleetBoards;
+pub use lobby_deposit::{DepositStatus, LobbyDeposit};
+pub use matchmaking::QueueEntry;
+pub use recent_games::{GameResult, MatchSummary};
+pub use reveal::BoardReveals;
+pub use spectators::Role;
+pub use timeline::GameTimeline;
+pub use tournament::{BracketMatch, Tournament};
+
+pub(crate) const BOARD_SIZE: u32 = 5;
+pub(crate) const ALL_CELLS: u32 = (1 << (BOARD_SIZE * BOARD_SIZE)) - 1;
+pub(crate) const TOTAL_SHIPS: u32 = 3;
+
+// Board hash used by practice games: the example board + salt 0xdeadbeef from
+// circuits/battleship/Prover.toml, so tutorial clients can prove against it.
+pub(crate) const PRACTICE_BOARD_HASH: [u8; 32] = [
+    0x0f, 0xc3, 0x90, 0xb5, 0x93, 0x5b, 0xa7, 0xf9,
+    0x5a, 0xb3, 0xb6, 0x03, 0x80, 0x4e, 0x7e, 0x2d,
+    0x4d, 0xe3, 0x90, 0xba, 0x44, 0x7a, 0xeb, 0xc5,
+    0xef, 0xfc, 0x49, 0xad, 0x38, 0xd0, 0x2f, 0x20,
+];
 
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
-    GameState,
+    ActiveGames(Address),
+    Blocked(Address),
+    BracketGame(u32),
+    Config,
+    CurrentGame,
+    Dispute(u32),
+    DrawOffer,
+    FinishedCount(Address),
+    FinishedGames(Address),
+    FleetBoards(u32, Address),
+    Game(u32),
+    GameId(u32),
+    GamesCreated,
+    GamesInPhase(GamePhase),
+    HubLock,
+    JoinNonce(Address),
+    LastFireNonce(Address),
+    LastEnqueue(Address),
+    LastJoin(Address),
+    LobbyDeposit(u32),
+    MoveProof(u32, u32),
+    MatchQueue,
+    Opponents(Address),
+    PendingBoard(u32),
+    PlainBoard(u32, Address),
+    Points(u32),
+    Reveals,
+    Spectators(u32),
+    Timeline,
     PlayerStats(Address),
+    ProofsVerified(u32),
+    RecentGames,
+    RematchProposal,
+    Result(u32),
+    ShipsSunk(u32),
+    ShotCancelled(u32),
+    ShotClock(u32),
+    Tournament(u32),
+    TournamentCount,
+}
+
+/// What happens to a wagered pot when a game ends by surrender or timeout
+/// rather than by sinking the fleet.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ForfeitPolicy {
+    WinnerTakesAll,
+    RefundOnForfeit,
+}
+
+/// What wagers are escrowed in. Native is XLM through its Stellar Asset
+/// Contract; Token is any SEP-41 token contract, including other SACs.
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+pub enum StakeAsset {
+    Disabled,
+    Native,
+    Token(Address),
+}
+
+/// How many hits end the game. HitsCount treats every hit as a sunk ship (the
+/// original 1×1-fleet rule); DistinctShipsSunk needs every cell of every ship
+/// hit, i.e. ships_to_sink × ship_cells hits. FirstToHits(n) ends the game at
+/// n hits regardless of fleet size. ProvenSinks counts ships, not hits: the
+/// defender proves which hits sank a ship (see sinks.rs), and the fleet is
+/// gone once ships_to_sink of them have. Points(n) weighs ships instead: the
+/// proof gives the value of each ship sunk, and n points win.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WinRule {
+    HitsCount,
+    DistinctShipsSunk,
+    FirstToHits(u32),
+    ProvenSinks,
+    Points(u32),
+}
+
+/// What happens when a shooter has fired at every cell without winning, which
+/// variant win rules make possible: the game is drawn or the shooter loses.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OutOfTargets {
+    Draw,
+    Loss,
+}
+
+/// Deployment-wide settings, fixed by initialize.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Seconds without a move before the waiting player may claim the game
+    /// (0 = none) — see inactivity.rs.
+    pub absence_window: u64,
+    pub admin: Address,
+    /// Lets a defender concede a single shot as a hit with skip_response.
+    pub allow_skip: bool,
+    /// Seconds players get to commit once the Commit phase begins (0 = none).
+    pub commit_timeout: u64,
+    /// Enables get_debug_snapshot; leave it off in production.
+    pub dev_mode: bool,
+    /// Seconds after a game finishes during which its players may dispute
+    /// the result (0 = none) — see dispute.rs.
+    pub dispute_window: u64,
+    /// Notify `hub` as matches start and end.
+    pub enable_hub: bool,
+    pub forfeit_policy: ForfeitPolicy,
+    /// Recomputes Poseidon2 board hashes for reveals; required for wagers.
+    pub hasher: Option<Address>,
+    pub hub: Option<Address>,
+    /// Escrowed by whoever opens a lobby (0 = none) — see lobby_deposit.rs.
+    pub lobby_deposit: i128,
+    /// Seconds before an unfilled lobby may be expired (0 = never).
+    pub lobby_ttl: u64,
+    /// Rejected proofs that disqualify a defender. At 0 a rejected proof
+    /// reverts instead of being recorded.
+    pub max_invalid_proofs: u32,
+    /// Exchanges before a game ends in a draw (0 = none) — see draw.rs.
+    pub max_moves: u32,
+    /// Ledgers that must close after Playing begins before anyone may fire
+    /// (0 = none), so a precomputed opening can't be fired instantly.
+    pub min_fire_delay: u32,
+    /// How a game ends when the shooter has no cell left to fire at.
+    pub out_of_targets: OutOfTargets,
+    /// Pause, instead of reverting, when the verifier can't be reached —
+    /// see inactivity.rs.
+    pub pause_on_verifier_error: bool,
+    /// Lets player2 take the first shot with invoke_swap.
+    pub pie_rule: bool,
+    /// Keep a shot's coordinates out of its events until it's answered.
+    pub private_shots: bool,
+    /// Circuit version proofs must be prefixed with (0 = unversioned).
+    pub proof_version: u32,
+    /// The admin's cut of a claimed pot, in basis points.
+    pub rake_bps: u32,
+    /// Seconds after a win to reveal both boards (0 = one day) — see reveal.rs.
+    pub reveal_window: u64,
+    /// What wagers (stake > 0) are escrowed in.
+    pub stake_asset: StakeAsset,
+    /// Extra seconds on the first turn of a game, to absorb clock skew.
+    pub start_grace_period: u64,
+    /// Per-turn deadline in seconds (0 = none).
+    pub turn_timeout: u64,
+    /// With this off only game_started and game_over are emitted.
+    pub verbose_events: bool,
+    /// UltraHonk verifier contract for submit_response proofs.
+    pub verifier: Option<Address>,
+    pub win_rule: WinRule,
 }
 
+/// `rating` starts at INITIAL_RATING and moves RATING_STEP per result.
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct PlayerStats {
+    pub best_hit_streak: u32,
+    pub forfeits: u32,
+    pub games_lost: u32,
     pub games_played: u32,
     pub games_won: u32,
+    pub rating: u32,
+}
+
+pub(crate) const INITIAL_RATING: u32 = 1200;
+const RATING_STEP: u32 = 16;
+
+/// The kind of game, fixed at creation. A Practice game skips the Commit
+/// phase and plays against PRACTICE_BOARD_HASH; a Blitz game runs a chess
+/// clock of `time_budget` seconds per player. CoOp(n) puts both players on
+/// one board against a scripted attacker for n shots — see coop.rs. Ammo(n)
+/// gives each player n shots; running out without winning loses (ammo.rs).
+/// Fleet(n) gives each player n grids, each with a full fleet, to be sunk
+/// on every grid (fleet.rs). Transparent plays with plaintext boards and no
+/// proofs (transparent.rs).
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameMode {
+    Classic,
+    Practice,
+    Blitz,
+    CoOp(u32),
+    Ammo(u32),
+    Fleet(u32),
+    Transparent,
+}
+
+/// Per-game settings chosen by the creator. `ships_to_sink_pN` is the size of
+/// player N's fleet, i.e. how many hits the opponent needs to win.
+/// `time_budget` is the Blitz clock in seconds, and must be 0 in other modes.
+/// `stake` is what each player escrows on joining; the winner takes the pot.
+/// `ship_cells` is the length of every ship in both fleets.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GameOptions {
+    pub mode: GameMode,
+    pub ship_cells: u32,
+    pub ships_to_sink_p1: u32,
+    pub ships_to_sink_p2: u32,
+    pub stake: i128,
+    pub time_budget: u64,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            mode: GameMode::Classic,
+            ship_cells: 1,
+            ships_to_sink_p1: TOTAL_SHIPS,
+            ships_to_sink_p2: TOTAL_SHIPS,
+            stake: 0,
+            time_budget: 0,
+        }
+    }
 }
 
 #[contracttype]
@@ -28,16 +301,30 @@ pub enum GamePhase {
     Commit,
     Playing,
     Finished,
+    // The verifier couldn't be reached; only submit_response can resume,
+    // until an inactivity claim may end it — see inactivity.rs
+    Paused,
 }
 
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct GameState {
-    pub board_hash_p1: BytesN<32>,
-    pub board_hash_p2: BytesN<32>,
+    pub board_hash_p1: Vec<BytesN<32>>,
+    pub board_hash_p2: Vec<BytesN<32>>,
+    pub fired_mask_p1: u32,
+    pub fired_mask_p2: u32,
     pub has_winner: bool,
+    pub hit_streak_p1: u32,
+    pub hit_mask_p1: u32,
+    pub hit_mask_p2: u32,
+    pub hit_streak_p2: u32,
     pub hits_on_p1: u32,
     pub hits_on_p2: u32,
+    pub invalid_proofs_p1: u32,
+    pub invalid_proofs_p2: u32,
+    pub last_move_at: u64,
+    pub mode: GameMode,
+    pub move_number: u32,
     pub p1_committed: bool,
     pub p1_joined: bool,
     pub p2_committed: bool,
@@ -48,14 +335,727 @@ pub struct GameState {
     pub phase: GamePhase,
     pub player1: Address,
     pub player2: Address,
+    pub pot: i128,
+    pub remaining_time_p1: u64,
+    pub remaining_time_p2: u64,
     pub session_id: u32,
+    pub ship_cells: u32,
+    pub ships_to_sink_p1: u32,
+    pub ships_to_sink_p2: u32,
     pub shots_fired_p1: u32,
     pub shots_fired_p2: u32,
+    pub stake: i128,
+    pub time_budget: u64,
     pub turn: Address,
+    pub turn_deadline: u64,
     pub winner: Address,
 }
 
-const NO_SHOT: u32 = u32::MAX;
+/// Lobby view without GameState's placeholders: `player2` is None until P2 joins.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Lobby {
+    pub phase: GamePhase,
+    pub player1: Address,
+    pub player2: Option<Address>,
+}
+
+// Largest board commitment accepted, in field elements
+const MAX_COMMITMENT_LEN: u32 = 8;
+
+pub(crate) const NO_SHOT: u32 = u32::MAX;
+
+fn start_playing(env: &Env, state: &mut GameState) {
+    state.phase = GamePhase::Playing;
+    state.turn = state.player1.clone();
+    state.last_move_at = env.ledger().timestamp();
+    stamp(env, |t| &mut t.playing_started_at);
+    stamp_playing_sequence(env);
+
+    let grace = load_config(env).map(|config| config.start_grace_period).unwrap_or(0);
+    state.turn_deadline = next_turn_deadline(env);
+    if state.turn_deadline > 0 {
+        state.turn_deadline += grace;
+    }
+
+    // The hub goes first, in case it hands the game a new session id
+    if !is_coop(state) {
+        call_hub_start(env, state);
+    }
+    publish(env, state, "game_started", (state.player1.clone(), state.player2.clone()));
+    if is_coop(state) {
+        coop::attack(env, state);
+    }
+}
+
+// 0 when the deployment has no turn timeout
+pub(crate) fn next_turn_deadline(env: &Env) -> u64 {
+    match load_config(env).map(|config| config.turn_timeout) {
+        Some(timeout) if timeout > 0 => env.ledger().timestamp() + timeout,
+        _ => 0,
+    }
+}
+
+pub(crate) fn pass_turn(env: &Env, state: &mut GameState, to: Address) {
+    charge_clock(env, state);
+    state.last_move_at = env.ledger().timestamp();
+    state.turn = to;
+    state.turn_deadline = next_turn_deadline(env);
+    publish(env, state, "turn_changed", state.turn.clone());
+}
+
+// Chess clock: bill the time since the last move to whoever held the turn.
+// Called right before the turn changes hands; no-op outside Blitz.
+fn charge_clock(env: &Env, state: &mut GameState) {
+    if state.mode != GameMode::Blitz {
+        return;
+    }
+    let now = env.ledger().timestamp();
+    let elapsed = now - state.last_move_at;
+    let remaining = if state.turn == state.player1 {
+        &mut state.remaining_time_p1
+    } else {
+        &mut state.remaining_time_p2
+    };
+    assert!(elapsed < *remaining, "Out of time");
+    *remaining -= elapsed;
+}
+
+// Every game and stats counter goes through here, so a runaway counter
+// reverts instead of wrapping.
+pub(crate) fn bump(counter: &mut u32) {
+    *counter = counter.checked_add(1).expect("COUNTER_OVERFLOW");
+}
+
+// Games live in persistent storage under their session id, with the instance
+// only pointing at the current one — so the instance doesn't grow with game
+// state, and finished games outlive a reset. Every save extends the game's
+// (and the instance's) TTL so a match in progress can't be archived.
+pub(crate) const GAME_TTL_THRESHOLD: u32 = 17_280; // ~1 day of ledgers
+pub(crate) const GAME_TTL_EXTEND_TO: u32 = 518_400; // ~30 days
+
+pub(crate) fn has_game(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::CurrentGame)
+}
+
+pub(crate) fn load_game(env: &Env) -> Option<GameState> {
+    let session_id = env.storage().instance().get::<DataKey, u32>(&DataKey::CurrentGame)?;
+    env.storage().persistent().get::<DataKey, GameState>(&DataKey::Game(session_id))
+}
+
+pub(crate) fn save_game(env: &Env, state: &GameState) {
+    let key = DataKey::Game(state.session_id);
+    let previous = env.storage().persistent().get::<DataKey, GameState>(&key).map(|s| s.phase);
+    reindex(env, state.session_id, previous, Some(state.phase.clone()));
+    env.storage().persistent().set(&key, state);
+    env.storage().persistent().extend_ttl(&key, GAME_TTL_THRESHOLD, GAME_TTL_EXTEND_TO);
+    env.storage().instance().set(&DataKey::CurrentGame, &state.session_id);
+    env.storage().instance().extend_ttl(GAME_TTL_THRESHOLD, GAME_TTL_EXTEND_TO);
+}
+
+pub(crate) fn load_config(env: &Env) -> Option<Config> {
+    env.storage().instance().get::<DataKey, Config>(&DataKey::Config)
+}
+
+// Every state-changing entry point but initialize starts here, so nothing
+// runs on hardcoded defaults before the deployment is configured.
+pub(crate) fn assert_initialized(env: &Env) {
+    assert!(env.storage().instance().has(&DataKey::Config), "NOT_INITIALIZED");
+}
+
+pub(crate) fn load_stats(env: &Env, player: &Address) -> PlayerStats {
+    env.storage()
+        .persistent()
+        .get::<DataKey, PlayerStats>(&DataKey::PlayerStats(player.clone()))
+        .unwrap_or(PlayerStats {
+            best_hit_streak: 0,
+            forfeits: 0,
+            games_lost: 0,
+            games_played: 0,
+            games_won: 0,
+            rating: INITIAL_RATING,
+        })
+}
+
+// Consecutive hits by the shooter, across responses; a miss resets it.
+fn update_hit_streak(env: &Env, state: &mut GameState, shooter: &Address, is_hit: bool) {
+    let streak = if *shooter == state.player1 {
+        &mut state.hit_streak_p1
+    } else {
+        &mut state.hit_streak_p2
+    };
+    if !is_hit {
+        *streak = 0;
+        return;
+    }
+    bump(streak);
+
+    let mut stats = load_stats(env, shooter);
+    if *streak > stats.best_hit_streak {
+        stats.best_hit_streak = *streak;
+        env.storage().persistent().set(&DataKey::PlayerStats(shooter.clone()), &stats);
+    }
+}
+
+// All storage effects of a game ending: the result, both players' stats,
+// the game indices and the saved GameState. No external calls here, so the
+// callers below can finish every write before touching another contract.
+fn record_result(env: &Env, state: &mut GameState, winner: &Address, forfeit: bool) {
+    state.winner = winner.clone();
+    state.has_winner = true;
+    state.phase = GamePhase::Finished;
+    stamp(env, |t| &mut t.finished_at);
+    open_reveal_window(env);
+
+    let loser = other_player(state, winner);
+    let mut stats = load_stats(env, &loser);
+    bump(&mut stats.games_played);
+    bump(&mut stats.games_lost);
+    stats.rating = stats.rating.saturating_sub(RATING_STEP);
+    if forfeit {
+        bump(&mut stats.forfeits);
+    }
+    let mut winner_stats = load_stats(env, winner);
+    bump(&mut winner_stats.games_played);
+    bump(&mut winner_stats.games_won);
+    winner_stats.rating = winner_stats.rating.saturating_add(RATING_STEP);
+
+    env.storage().persistent().set(&DataKey::PlayerStats(loser.clone()), &stats);
+    env.storage().persistent().set(&DataKey::PlayerStats(winner.clone()), &winner_stats);
+    track_finished(env, state);
+    track_opponents(env, winner, &loser);
+    push_recent(env, GameResult {
+        finished_at: env.ledger().timestamp(),
+        forfeit,
+        loser,
+        session_id: state.session_id,
+        winner: winner.clone(),
+    });
+    save_game(env, state);
+}
+
+// Moves one win from `winner` and one loss from `loser` on their records,
+// or takes them back off with `undo`
+pub(crate) fn shift_stats(env: &Env, winner: &Address, loser: &Address, undo: bool) {
+    let mut winner_stats = load_stats(env, winner);
+    let mut loser_stats = load_stats(env, loser);
+    if undo {
+        winner_stats.games_won = winner_stats.games_won.saturating_sub(1);
+        winner_stats.rating = winner_stats.rating.saturating_sub(RATING_STEP);
+        loser_stats.games_lost = loser_stats.games_lost.saturating_sub(1);
+        loser_stats.rating = loser_stats.rating.saturating_add(RATING_STEP);
+    } else {
+        winner_stats.games_won = winner_stats.games_won.saturating_add(1);
+        winner_stats.rating = winner_stats.rating.saturating_add(RATING_STEP);
+        loser_stats.games_lost = loser_stats.games_lost.saturating_add(1);
+        loser_stats.rating = loser_stats.rating.saturating_sub(RATING_STEP);
+    }
+    env.storage().persistent().set(&DataKey::PlayerStats(winner.clone()), &winner_stats);
+    env.storage().persistent().set(&DataKey::PlayerStats(loser.clone()), &loser_stats);
+}
+
+// Hands a recorded win to the other player, stats and stored result included
+pub(crate) fn reassign_win(env: &Env, state: &mut GameState) {
+    let old_winner = state.winner.clone();
+    let new_winner = other_player(state, &old_winner);
+    shift_stats(env, &old_winner, &new_winner, true);
+    shift_stats(env, &new_winner, &old_winner, false);
+    state.winner = new_winner.clone();
+    amend_result(env, state.session_id, Some((new_winner, old_winner)));
+}
+
+pub(crate) fn finish_game(env: &Env, state: &mut GameState, winner: Address) {
+    record_result(env, state, &winner, false);
+
+    // Any remaining pot stays escrowed until both boards are revealed —
+    // see claim_winnings
+    publish(env, state, "game_over", state.winner.clone());
+    call_hub_end(env, state);
+}
+
+// Surrender and timeouts: same as finish_game, except that under
+// RefundOnForfeit both stakes go back and there is no pot left to claim.
+// So do they before both boards are committed: an uncommitted winner has no
+// board to reveal, so the pot could only ever go to the loser.
+// The loss also counts as a forfeit in the loser's stats.
+pub(crate) fn forfeit_game(env: &Env, state: &mut GameState, winner: Address) {
+    let policy = load_config(env)
+        .map(|config| config.forfeit_policy)
+        .unwrap_or(ForfeitPolicy::WinnerTakesAll);
+    let committed = state.p1_committed && state.p2_committed;
+    let refund = state.pot > 0 && (policy == ForfeitPolicy::RefundOnForfeit || !committed);
+    if refund {
+        state.pot = 0;
+    }
+    record_result(env, state, &winner, true);
+
+    if refund {
+        let token = stake_token(env);
+        let contract = env.current_contract_address();
+        token.transfer(&contract, &state.player1, &state.stake);
+        token.transfer(&contract, &state.player2, &state.stake);
+    }
+    publish(env, state, "game_over", state.winner.clone());
+    call_hub_end(env, state);
+}
+
+pub(crate) fn hits_to_win(env: &Env, state: &GameState, defender: &Address) -> u32 {
+    let ships_to_sink = if *defender == state.player1 {
+        state.ships_to_sink_p1
+    } else {
+        state.ships_to_sink_p2
+    };
+    match win_rule(env) {
+        WinRule::HitsCount | WinRule::ProvenSinks => ships_to_sink,
+        WinRule::DistinctShipsSunk => ships_to_sink * state.ship_cells,
+        WinRule::FirstToHits(hits) | WinRule::Points(hits) => hits,
+    }
+}
+
+pub(crate) fn win_rule(env: &Env) -> WinRule {
+    load_config(env)
+        .map(|config| config.win_rule)
+        .unwrap_or(WinRule::HitsCount)
+}
+
+// Count a hit on `defender` (recording `cell` in their hit mask, and a sunk
+// ship if the proof says it sank one worth `sunk_value`) and finish the game
+// if that sinks the fleet. Returns whether the game is over.
+fn score_hit(
+    env: &Env,
+    state: &mut GameState,
+    defender: &Address,
+    shooter: Address,
+    cell: u32,
+    sunk_value: u32,
+) -> bool {
+    // A fleet game keeps its hit masks per grid instead (fleet.rs)
+    let mask_cell = if fleet_size(state).is_some() { 0 } else { cell };
+    let hits = if *defender == state.player1 {
+        bump(&mut state.hits_on_p1);
+        state.hit_mask_p1 |= mask_cell;
+        state.hits_on_p1
+    } else {
+        bump(&mut state.hits_on_p2);
+        state.hit_mask_p2 |= mask_cell;
+        state.hits_on_p2
+    };
+    if sunk_value > 0 {
+        record_sink(env, state, defender, sunk_value);
+    }
+
+    let target = hits_to_win(env, state, defender);
+    let boards_cleared = fleet_size(state).map(|_| record_hit(env, state, defender, cell, target));
+    let fleet_sunk = match win_rule(env) {
+        WinRule::ProvenSinks => {
+            ships_sunk(env, state, defender) >= target * fleet_size(state).unwrap_or(1)
+        }
+        WinRule::Points(_) => points_scored(env, state, &shooter) >= target,
+        _ => boards_cleared.unwrap_or(hits >= target),
+    };
+    if !fleet_sunk {
+        return false;
+    }
+    // Game over — shooter wins
+    finish_game(env, state, shooter);
+    true
+}
+
+// A shooter who has fired everywhere without winning can never move again,
+// so the game ends as Config.out_of_targets says instead of stalling.
+fn check_out_of_targets(env: &Env, state: &mut GameState, shooter: &Address) {
+    let out = if fleet_size(state).is_some() {
+        all_fired(env, state, shooter)
+    } else {
+        fired_mask(state, shooter) == ALL_CELLS
+    };
+    if state.phase != GamePhase::Playing || !out {
+        return;
+    }
+    publish(env, state, "out_of_targets", shooter.clone());
+    let outcome = load_config(env)
+        .map(|config| config.out_of_targets)
+        .unwrap_or(OutOfTargets::Draw);
+    match outcome {
+        OutOfTargets::Draw => end_in_draw(env, state),
+        OutOfTargets::Loss => finish_game(env, state, other_player(state, shooter)),
+    }
+}
+
+// Bit index of a cell in the fired masks (row-major, like the circuit's board)
+pub(crate) fn cell_bit(x: u32, y: u32) -> u32 {
+    1 << (y * BOARD_SIZE + x)
+}
+
+// Takes (x, y) back out of the cells `shooter` has fired at
+fn unfire(env: &Env, state: &mut GameState, shooter: &Address, x: u32, y: u32) {
+    if fleet_size(state).is_some() {
+        unaim(env, state, shooter, cell_bit(x, y));
+    } else if *shooter == state.player1 {
+        state.fired_mask_p1 &= !cell_bit(x, y);
+    } else {
+        state.fired_mask_p2 &= !cell_bit(x, y);
+    }
+}
+
+// Cells of grid `board` that `attacker` has fired at; board 0 is the only
+// grid outside fleet mode
+pub(crate) fn fired_cells(env: &Env, state: &GameState, attacker: &Address, board: u32) -> u32 {
+    assert!(*attacker == state.player1 || *attacker == state.player2, "Not a player");
+    if fleet_size(state).is_some() {
+        fired_at(env, state, attacker, board)
+    } else {
+        fired_mask(state, attacker)
+    }
+}
+
+fn fired_mask(state: &GameState, attacker: &Address) -> u32 {
+    if *attacker == state.player1 {
+        state.fired_mask_p1
+    } else if *attacker == state.player2 {
+        state.fired_mask_p2
+    } else {
+        panic!("Not a player");
+    }
+}
+
+pub(crate) fn other_player(state: &GameState, player: &Address) -> Address {
+    if *player == state.player1 {
+        state.player2.clone()
+    } else {
+        state.player1.clone()
+    }
+}
+
+// How many games this contract has created; mixed into the session id so two
+// games opened in one ledger by the same player still get different ids
+fn games_created(env: &Env) -> u32 {
+    env.storage().instance().get::<DataKey, u32>(&DataKey::GamesCreated).unwrap_or(0)
+}
+
+// First four bytes of sha256(player1 ‖ timestamp ‖ sequence ‖ games_created),
+// big-endian.
+fn game_id(env: &Env, player1: &Address, timestamp: u64, sequence: u32) -> u32 {
+    let mut preimage = player1.clone().to_xdr(env);
+    preimage.append(&Bytes::from_array(env, &timestamp.to_be_bytes()));
+    preimage.append(&Bytes::from_array(env, &sequence.to_be_bytes()));
+    preimage.append(&Bytes::from_array(env, &games_created(env).to_be_bytes()));
+    let digest = env.crypto().sha256(&preimage).to_array();
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+// Fresh lobby state; both player slots hold `player` until someone joins.
+// The session id is derived from the creator, the current ledger and the
+// game count, and may never repeat.
+pub(crate) fn new_game(env: &Env, player: &Address, options: &GameOptions) -> GameState {
+    assert_reveals_checkable(env, options.stake, options.mode);
+    let session_id = game_id(env, player, env.ledger().timestamp(), env.ledger().sequence());
+    let id_key = DataKey::GameId(session_id);
+    assert!(!env.storage().persistent().has(&id_key), "Game id collision");
+    env.storage().persistent().set(&id_key, &());
+    let mut created = games_created(env);
+    bump(&mut created);
+    env.storage().instance().set(&DataKey::GamesCreated, &created);
+    env.storage().instance().remove(&DataKey::Timeline);
+    stamp(env, |t| &mut t.created_at);
+
+    GameState {
+        player1: player.clone(),
+        player2: player.clone(),
+        board_hash_p1: Vec::new(env),
+        board_hash_p2: Vec::new(env),
+        hits_on_p1: 0,
+        hits_on_p2: 0,
+        hit_streak_p1: 0,
+        hit_streak_p2: 0,
+        hit_mask_p1: 0,
+        hit_mask_p2: 0,
+        invalid_proofs_p1: 0,
+        invalid_proofs_p2: 0,
+        fired_mask_p1: 0,
+        fired_mask_p2: 0,
+        shots_fired_p1: 0,
+        shots_fired_p2: 0,
+        turn: player.clone(),
+        phase: GamePhase::WaitingForPlayers,
+        pending_shot_x: NO_SHOT,
+        pending_shot_y: NO_SHOT,
+        pending_shooter: player.clone(),
+        winner: player.clone(),
+        has_winner: false,
+        p1_committed: false,
+        p2_committed: false,
+        p1_joined: false,
+        p2_joined: false,
+        mode: options.mode,
+        session_id,
+        ship_cells: options.ship_cells,
+        ships_to_sink_p1: options.ships_to_sink_p1,
+        ships_to_sink_p2: options.ships_to_sink_p2,
+        time_budget: options.time_budget,
+        remaining_time_p1: options.time_budget,
+        remaining_time_p2: options.time_budget,
+        last_move_at: 0,
+        move_number: 0,
+        turn_deadline: 0,
+        stake: options.stake,
+        pot: 0,
+    }
+}
+
+// XDR of Asset::Native — the SAC address is derived from it
+const NATIVE_ASSET_XDR: [u8; 4] = [0, 0, 0, 0];
+
+pub(crate) fn native_asset(env: &Env) -> Address {
+    env.deployer()
+        .with_stellar_asset(Bytes::from_array(env, &NATIVE_ASSET_XDR))
+        .deployed_address()
+}
+
+pub(crate) fn stake_token(env: &Env) -> token::Client<'_> {
+    let asset = load_config(env).map(|config| config.stake_asset);
+    let token = match asset {
+        Some(StakeAsset::Native) => native_asset(env),
+        Some(StakeAsset::Token(token)) => token,
+        Some(StakeAsset::Disabled) | None => panic!("Staking not configured"),
+    };
+    token::Client::new(env, &token)
+}
+
+// Every reason `shooter` may not fire at (x, y) right now, in the order
+// fire_shot reports them. Shared by fire and the can_fire views; `board` is
+// as for fire.
+pub(crate) fn validate_shot(
+    env: &Env,
+    state: &GameState,
+    shooter: &Address,
+    board: u32,
+    x: u32,
+    y: u32,
+) -> Result<(), &'static str> {
+    if x >= BOARD_SIZE {
+        return Err("x out of range");
+    }
+    if y >= BOARD_SIZE {
+        return Err("y out of range");
+    }
+    if *shooter != state.player1 && *shooter != state.player2 {
+        return Err("NOT_A_PLAYER");
+    }
+    if state.phase != GamePhase::Playing {
+        return Err("Not playing");
+    }
+    if state.turn != *shooter {
+        return Err("Not your turn");
+    }
+    if state.pending_shot_x != NO_SHOT {
+        return Err("Shot pending");
+    }
+    check_board_index(state, board)?;
+    if fired_cells(env, state, shooter, board) & cell_bit(x, y) != 0 {
+        return Err("Already fired");
+    }
+    let delay = load_config(env).map(|config| config.min_fire_delay).unwrap_or(0);
+    let earliest = load_timeline(env).playing_started_sequence.saturating_add(delay);
+    if delay > 0 && env.ledger().sequence() < earliest {
+        return Err("Too early to fire");
+    }
+    if shots_remaining(state, shooter) == Some(0) {
+        return Err("Out of ammo");
+    }
+    // Only reachable in wagered games, which wait for propose_draw
+    if move_limit_reached(env, state) {
+        return Err("Move limit reached");
+    }
+    Ok(())
+}
+
+// Shared by fire_shot, fire_shot_with_nonce and fire_at_board; the caller
+// has already authorized `shooter`. `board` picks the grid in a fleet game
+// and must be 0 otherwise.
+pub(crate) fn fire(env: &Env, shooter: Address, board: u32, x: u32, y: u32) {
+    let mut state = load_game(env).expect("No game");
+    if let Err(reason) = validate_shot(env, &state, &shooter, board, x, y) {
+        panic!("{}", reason);
+    }
+    assert!(!is_coop(&state), "Co-op has no human shooters");
+
+    if fleet_size(&state).is_some() {
+        aim(env, &state, &shooter, board, cell_bit(x, y));
+    } else if shooter == state.player1 {
+        state.fired_mask_p1 |= cell_bit(x, y);
+    } else {
+        state.fired_mask_p2 |= cell_bit(x, y);
+    }
+
+    state.pending_shot_x = x;
+    state.pending_shot_y = y;
+    state.pending_shooter = shooter.clone();
+
+    if shooter == state.player1 {
+        bump(&mut state.shots_fired_p1);
+    } else {
+        bump(&mut state.shots_fired_p2);
+    }
+
+    save_shot_clock(env, &state, &shooter);
+    let defender = other_player(&state, &shooter);
+    pass_turn(env, &mut state, defender);
+    if private_shots(env) {
+        publish(env, &state, "shot_fired", shooter);
+    } else {
+        publish(env, &state, "shot_fired", (shooter, x, y));
+    }
+
+    save_game(env, &state);
+}
+
+// The shooter's turn as it stood before firing — turn_deadline, last_move_at
+// and their Blitz clock — so cancel_shot can hand it back unchanged
+fn save_shot_clock(env: &Env, state: &GameState, shooter: &Address) {
+    let clock = if *shooter == state.player1 {
+        state.remaining_time_p1
+    } else {
+        state.remaining_time_p2
+    };
+    let key = DataKey::ShotClock(state.session_id);
+    env.storage().persistent().set(&key, &(state.turn_deadline, state.last_move_at, clock));
+}
+
+// One join per address per ledger — keeps lobby spam in check. Matchmaking
+// counts the games it opens as joins too.
+pub(crate) fn rate_limit_join(env: &Env, player: &Address) {
+    rate_limit(env, DataKey::LastJoin(player.clone()));
+}
+
+// Panics if `key` was already stamped this ledger, then stamps it
+pub(crate) fn rate_limit(env: &Env, key: DataKey) {
+    let sequence = env.ledger().sequence();
+    let last = env.storage().temporary().get::<DataKey, u32>(&key);
+    assert!(last != Some(sequence), "RATE_LIMITED");
+    env.storage().temporary().set(&key, &sequence);
+}
+
+// Shared by join_game and create_game — `options` only applies when this join
+// opens a new lobby.
+pub(crate) fn join(env: &Env, player: Address, options: GameOptions) {
+    assert!(!is_blocked(env, &player), "ADDRESS_BLOCKED");
+    rate_limit_join(env, &player);
+
+    let mut state = load_game(env)
+        .unwrap_or_else(|| new_game(env, &player, &options));
+
+    assert!(state.phase == GamePhase::WaitingForPlayers, "Game already started");
+
+    if !state.p1_joined {
+        state.player1 = player.clone();
+        state.p1_joined = true;
+        take_deposit(env, &player, state.session_id);
+    } else {
+        assert!(state.player1 != player, "Already joined as P1");
+        state.player2 = player.clone();
+        state.p2_joined = true;
+        state.phase = GamePhase::Commit;
+        stamp(env, |t| &mut t.commit_started_at);
+        refund_deposit(env, state.session_id);
+
+        if state.mode == GameMode::Practice {
+            start_practice(env, &mut state);
+        }
+    }
+
+    if state.stake > 0 {
+        stake_token(env).transfer(&player, &env.current_contract_address(), &state.stake);
+        state.pot += state.stake;
+    }
+
+    track_joined(env, &player, state.session_id);
+    save_game(env, &state);
+}
+
+// Both boards default to the known practice layout — straight to Playing
+pub(crate) fn start_practice(env: &Env, state: &mut GameState) {
+    let practice_hash = Vec::from_array(env, [BytesN::from_array(env, &PRACTICE_BOARD_HASH)]);
+    state.board_hash_p1 = practice_hash.clone();
+    state.board_hash_p2 = practice_hash;
+    state.p1_committed = true;
+    state.p2_committed = true;
+    start_playing(env, state);
+}
+
+// Drops the current game. An unfinished wagered game hands every joined
+// player their stake back, and an unfilled lobby its deposit.
+pub(crate) fn clear_game(env: &Env) {
+    if let Some(mut state) = load_game(env) {
+        assert!(!dispute_open(env, &state), "Dispute open");
+        cancel_rematch(env, &state);
+        // Finished games stay readable through get_game
+        if state.phase != GamePhase::Finished {
+            track_abandoned(env, &state);
+            reindex(env, state.session_id, Some(state.phase.clone()), None);
+            env.storage().persistent().remove(&DataKey::Game(state.session_id));
+        }
+        if state.pot > 0 && state.phase == GamePhase::Finished {
+            settle_pot(env, &mut state);
+        } else if state.pot > 0 {
+            let token = stake_token(env);
+            let contract = env.current_contract_address();
+            token.transfer(&contract, &state.player1, &state.stake);
+            if state.p2_joined {
+                token.transfer(&contract, &state.player2, &state.stake);
+            }
+        }
+        refund_deposit(env, state.session_id);
+    }
+
+    env.storage().instance().remove(&DataKey::CurrentGame);
+    env.storage().instance().remove(&DataKey::DrawOffer);
+    env.storage().instance().remove(&DataKey::Reveals);
+    env.storage().instance().remove(&DataKey::Timeline);
+}
+
+// Shared by commit_board_elements and join_both, after `player`'s auth
+pub(crate) fn commit(env: &Env, player: Address, board_hash: Vec<BytesN<32>>) {
+    assert!(!board_hash.is_empty(), "Empty commitment");
+    assert!(board_hash.len() <= MAX_COMMITMENT_LEN, "Commitment too long");
+
+    let mut state = load_game(env).expect("No game");
+
+    match state.phase {
+        GamePhase::Commit => {}
+        GamePhase::WaitingForPlayers => panic!("WAITING_FOR_PLAYERS"),
+        _ => panic!("COMMIT_PHASE_OVER"),
+    }
+    if let Some(boards) = fleet_size(&state) {
+        assert!(board_hash.len() == boards, "One hash per board");
+    }
+    if state.mode == GameMode::Transparent {
+        assert!(plain_board(env, &state, &player).is_some(), "Commit the plain board");
+    }
+
+    if player == state.player1 {
+        assert!(!state.p1_committed, "P1 already committed");
+        state.board_hash_p1 = board_hash;
+        state.p1_committed = true;
+    } else if player == state.player2 {
+        assert!(!state.p2_committed, "P2 already committed");
+        state.board_hash_p2 = board_hash;
+        state.p2_committed = true;
+    } else {
+        panic!("Not a player");
+    }
+
+    publish(env, &state, "board_committed", player);
+
+    if state.p1_committed && state.p2_committed {
+        if is_coop(&state) {
+            assert!(state.board_hash_p1 == state.board_hash_p2, "Co-op boards differ");
+        }
+        start_playing(env, &mut state);
+    }
+
+    save_game(env, &state);
+}
 
 #[contract]
 pub struct BattleshipContract;
@@ -63,120 +1063,190 @@ pub struct BattleshipContract;
 #[contractimpl]
 impl BattleshipContract {
 
+    pub fn initialize(env: Env, config: Config) {
+        assert_not_reentrant(&env);
+        config.admin.require_auth();
+        assert!(!env.storage().instance().has(&DataKey::Config), "Already initialized");
+        assert!(
+            !matches!(config.win_rule, WinRule::FirstToHits(0) | WinRule::Points(0)),
+            "Invalid win rule"
+        );
+        assert!(config.rake_bps <= 10_000, "Invalid rake");
+        assert!(config.lobby_deposit >= 0, "Invalid lobby deposit");
+        assert!(
+            config.lobby_deposit == 0 || config.stake_asset != StakeAsset::Disabled,
+            "Lobby deposit needs a stake asset"
+        );
+        env.storage().instance().set(&DataKey::Config, &config);
+    }
+
+    pub fn get_config(env: Env) -> Option<Config> {
+        load_config(&env)
+    }
+
+    /// The session id the next game gets if `player1` creates it at this
+    /// ledger timestamp and sequence, so clients can predict it.
+    /// Creating any other game first changes the answer.
+    pub fn derive_game_id(env: Env, player1: Address, timestamp: u64, sequence: u32) -> u32 {
+        game_id(&env, &player1, timestamp, sequence)
+    }
+
     pub fn join_game(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
         player.require_auth();
+        join(&env, player, GameOptions::default());
+    }
 
-        let zero_hash: BytesN<32> = BytesN::from_array(&env, &[0u8; 32]);
-
-        let mut state = env.storage()
-            .instance()
-            .get::<DataKey, GameState>(&DataKey::GameState)
-            .unwrap_or(GameState {
-                player1: player.clone(),
-                player2: player.clone(),
-                board_hash_p1: zero_hash.clone(),
-                board_hash_p2: zero_hash.clone(),
-                hits_on_p1: 0,
-                hits_on_p2: 0,
-                shots_fired_p1: 0,
-                shots_fired_p2: 0,
-                turn: player.clone(),
-                phase: GamePhase::WaitingForPlayers,
-                pending_shot_x: NO_SHOT,
-                pending_shot_y: NO_SHOT,
-                pending_shooter: player.clone(),
-                winner: player.clone(),
-                has_winner: false,
-                p1_committed: false,
-                p2_committed: false,
-                p1_joined: false,
-                p2_joined: false,
-                session_id: env.ledger().sequence(),
-            });
-
-        assert!(state.phase == GamePhase::WaitingForPlayers, "Game already started");
+    // Opens a lobby with custom settings — the creator becomes P1
+    pub fn create_game(env: Env, player: Address, options: GameOptions) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
 
-        if !state.p1_joined {
-            state.player1 = player.clone();
-            state.p1_joined = true;
+        assert!(!has_game(&env), "Game already exists");
+        assert!(options.ship_cells > 0, "Invalid ship length");
+        let max_ships = BOARD_SIZE * BOARD_SIZE / options.ship_cells;
+        assert!(
+            options.ships_to_sink_p1 > 0 && options.ships_to_sink_p1 <= max_ships,
+            "Invalid ship count for P1"
+        );
+        assert!(
+            options.ships_to_sink_p2 > 0 && options.ships_to_sink_p2 <= max_ships,
+            "Invalid ship count for P2"
+        );
+        assert!(options.stake >= 0, "Invalid stake");
+        if let GameMode::CoOp(turns) = options.mode {
+            assert!(turns > 0 && turns <= BOARD_SIZE * BOARD_SIZE, "Invalid co-op length");
+            assert!(options.stake == 0, "Co-op games are unwagered");
+        }
+        if let GameMode::Ammo(shots) = options.mode {
+            assert!(shots > 0, "Invalid ammo");
+        }
+        if let GameMode::Fleet(boards) = options.mode {
+            assert!((2..=MAX_COMMITMENT_LEN).contains(&boards), "Invalid fleet size");
+            // A fleet can't be revealed, so nothing could release its pot
+            assert!(options.stake == 0, "Fleet games are unwagered");
+        }
+        if options.mode == GameMode::Transparent {
+            // Sinks are only ever attested by the sink circuit
+            assert!(
+                !matches!(win_rule(&env), WinRule::ProvenSinks | WinRule::Points(_)),
+                "Win rule needs proofs"
+            );
+        }
+        if options.mode == GameMode::Blitz {
+            assert!(options.time_budget > 0, "Blitz needs a time budget");
         } else {
-            assert!(state.player1 != player, "Already joined as P1");
-            state.player2 = player.clone();
-            state.p2_joined = true;
-            state.phase = GamePhase::Commit;
+            assert!(options.time_budget == 0, "Time budget is blitz-only");
         }
 
-        env.storage().instance().set(&DataKey::GameState, &state);
+        join(&env, player, options);
     }
 
     pub fn reset_game(env: Env, caller: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
         caller.require_auth();
-        // Anyone can reset — simplifies demo flow
-        env.storage().instance().remove(&DataKey::GameState);
+        // Anyone can reset — simplifies demo flow — except a wagered game in
+        // play, where the refund would let the losing side walk away whole
+        if let Some(state) = load_game(&env) {
+            let in_play = matches!(state.phase, GamePhase::Playing | GamePhase::Paused);
+            if state.pot > 0 && in_play {
+                let admin = load_config(&env).expect("Not initialized").admin;
+                assert!(caller == admin, "Wagered game in progress");
+            }
+        }
+        clear_game(&env);
     }
 
+    /// Single-field commitment — the common case of commit_board_elements.
     pub fn commit_board(env: Env, player: Address, board_hash: BytesN<32>) {
-        player.require_auth();
+        let commitment = Vec::from_array(&env, [board_hash]);
+        Self::commit_board_elements(env, player, commitment);
+    }
 
-        let mut state = env.storage()
-            .instance()
-            .get::<DataKey, GameState>(&DataKey::GameState)
-            .expect("No game");
+    /// Commit to a board as several field elements, for layouts too large to
+    /// fit one Poseidon2 output. The verifier receives them all, in order.
+    pub fn commit_board_elements(env: Env, player: Address, board_hash: Vec<BytesN<32>>) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+        commit(&env, player, board_hash);
+    }
 
-        assert!(state.phase == GamePhase::Commit, "Not in commit phase");
+    pub fn fire_shot(env: Env, shooter: Address, x: u32, y: u32) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        shooter.require_auth();
+        fire(&env, shooter, 0, x, y);
+    }
 
-        if player == state.player1 {
-            assert!(!state.p1_committed, "P1 already committed");
-            state.board_hash_p1 = board_hash;
-            state.p1_committed = true;
-        } else if player == state.player2 {
-            assert!(!state.p2_committed, "P2 already committed");
-            state.board_hash_p2 = board_hash;
-            state.p2_committed = true;
-        } else {
-            panic!("Not a player");
-        }
+    /// fire_shot for clients that retry: a repeat of the shooter's last
+    /// `nonce` is a no-op instead of a second shot or a confusing error.
+    pub fn fire_shot_with_nonce(env: Env, shooter: Address, x: u32, y: u32, nonce: u64) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        shooter.require_auth();
 
-        if state.p1_committed && state.p2_committed {
-            state.phase = GamePhase::Playing;
-            state.turn = state.player1.clone();
+        let key = DataKey::LastFireNonce(shooter.clone());
+        if env.storage().persistent().get::<DataKey, u64>(&key) == Some(nonce) {
+            return;
         }
-
-        env.storage().instance().set(&DataKey::GameState, &state);
+        fire(&env, shooter, 0, x, y);
+        env.storage().persistent().set(&key, &nonce);
     }
 
-    pub fn fire_shot(env: Env, shooter: Address, x: u32, y: u32) {
+    /// Take back an unanswered shot: the cell is un-fired and the turn
+    /// returns to the shooter with the deadline and clock it had before the
+    /// shot. One cancel per turn.
+    pub fn cancel_shot(env: Env, shooter: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
         shooter.require_auth();
 
-        assert!(x < 5, "x out of range");
-        assert!(y < 5, "y out of range");
-
-        let mut state = env.storage()
-            .instance()
-            .get::<DataKey, GameState>(&DataKey::GameState)
-            .expect("No game");
+        let mut state = load_game(&env).expect("No game");
 
         assert!(state.phase == GamePhase::Playing, "Not playing");
-        assert!(state.turn == shooter, "Not your turn");
-        assert!(state.pending_shot_x == NO_SHOT, "Shot pending");
-
-        state.pending_shot_x = x;
-        state.pending_shot_y = y;
-        state.pending_shooter = shooter.clone();
+        assert!(state.pending_shot_x != NO_SHOT, "No pending shot");
+        assert!(state.pending_shooter == shooter, "Not your shot");
+        let cancelled = DataKey::ShotCancelled(state.session_id);
+        let last_cancel = env.storage().persistent().get::<DataKey, u32>(&cancelled);
+        assert!(last_cancel != Some(state.move_number), "Already cancelled this turn");
+        env.storage().persistent().set(&cancelled, &state.move_number);
 
+        let (x, y) = (state.pending_shot_x, state.pending_shot_y);
+        unfire(&env, &mut state, &shooter, x, y);
         if shooter == state.player1 {
-            state.shots_fired_p1 += 1;
+            state.shots_fired_p1 -= 1;
         } else {
-            state.shots_fired_p2 += 1;
+            state.shots_fired_p2 -= 1;
         }
+        state.pending_shot_x = NO_SHOT;
+        state.pending_shot_y = NO_SHOT;
 
-        state.turn = if shooter == state.player1 {
-            state.player2.clone()
+        // No charge_clock: the defender's time doesn't run on a cancelled shot
+        let (deadline, last_move_at, clock) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, (u64, u64, u64)>(&DataKey::ShotClock(state.session_id))
+            .expect("No pending shot");
+        if shooter == state.player1 {
+            state.remaining_time_p1 = clock;
         } else {
-            state.player1.clone()
-        };
+            state.remaining_time_p2 = clock;
+        }
+        state.turn = shooter.clone();
+        state.turn_deadline = deadline;
+        state.last_move_at = last_move_at;
+        publish(&env, &state, "turn_changed", state.turn.clone());
+        if private_shots(&env) {
+            publish(&env, &state, "shot_cancelled", shooter);
+        } else {
+            publish(&env, &state, "shot_cancelled", (shooter, x, y));
+        }
 
-        env.storage().instance().set(&DataKey::GameState, &state);
+        save_game(&env, &state);
     }
 
     pub fn submit_response(
@@ -184,102 +1254,527 @@ impl BattleshipContract {
         defender: Address,
         x: u32,
         y: u32,
-        is_hit: bool,
         proof: Bytes,
     ) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
         defender.require_auth();
 
-        let mut state = env.storage()
-            .instance()
-            .get::<DataKey, GameState>(&DataKey::GameState)
-            .expect("No game");
+        let mut state = load_game(&env).expect("No game");
 
-        assert!(state.phase == GamePhase::Playing, "Not playing");
+        assert!(
+            state.phase == GamePhase::Playing || state.phase == GamePhase::Paused,
+            "Not playing"
+        );
         assert!(state.pending_shot_x != NO_SHOT, "No pending shot");
         assert!(state.pending_shot_x == x, "x mismatch");
         assert!(state.pending_shot_y == y, "y mismatch");
+        // fire_shot bounds-checked the shot, but what's read back from
+        // storage is checked again before it reaches the verifier or a hit
+        // mask — cheap insurance against a corrupted pending shot
+        assert!(x < BOARD_SIZE && y < BOARD_SIZE, "Pending shot out of bounds");
         assert!(defender != state.pending_shooter, "Shooter can't respond");
         assert!(state.turn == defender, "Not your turn");
-        assert!(proof.len() >= 32, "Proof too short");
+
+        // Verify before anything below — a winning hit is only final once
+        // proven. A transparent game answers from the plaintext board instead.
+        let outcome = match plain_board(&env, &state, &defender) {
+            Some(board) => Some((board & cell_bit(x, y) != 0, 0)),
+            None => verify_zk_proof(&env, &mut state, &defender, x, y, &proof),
+        };
+        let Some((is_hit, sunk_value)) = outcome else {
+            save_game(&env, &state);
+            return;
+        };
 
         // Save shooter before clearing — needed for turn/winner assignment
         let shooter = state.pending_shooter.clone();
 
-        // Clear pending shot
+        // Clear pending shot — the exchange is complete
         state.pending_shot_x = NO_SHOT;
         state.pending_shot_y = NO_SHOT;
+        record_proof_hash(&env, &state, state.move_number, &proof);
+        bump(&mut state.move_number);
+        publish(&env, &state, "shot_result", (defender.clone(), x, y, is_hit));
 
-        if is_hit {
-            if defender == state.player1 {
-                state.hits_on_p1 += 1;
-            } else {
-                state.hits_on_p2 += 1;
-            }
+        if is_coop(&state) {
+            coop::respond(&env, &mut state, &defender, x, y, is_hit);
+            save_game(&env, &state);
+            return;
+        }
 
-            let hits = if defender == state.player1 {
-                state.hits_on_p1
-            } else {
-                state.hits_on_p2
-            };
-
-            if hits >= TOTAL_SHIPS {
-                // Game over — shooter wins
-                state.winner = shooter.clone();
-                state.has_winner = true;
-                state.phase = GamePhase::Finished;
-
-                // Update winner stats
-                let mut stats = env.storage()
-                    .persistent()
-                    .get::<DataKey, PlayerStats>(&DataKey::PlayerStats(shooter.clone()))
-                    .unwrap_or(PlayerStats { games_played: 0, games_won: 0 });
-                stats.games_played += 1;
-                stats.games_won += 1;
-                env.storage().persistent().set(&DataKey::PlayerStats(shooter), &stats);
-
-                env.storage().instance().set(&DataKey::GameState, &state);
-                return; // ← early return, don't update turn
-            }
+        update_hit_streak(&env, &mut state, &shooter, is_hit);
+
+        let cell = cell_bit(x, y);
+        if is_hit && score_hit(&env, &mut state, &defender, shooter.clone(), cell, sunk_value) {
+            save_game(&env, &state);
+            return; // ← early return, don't update turn
         }
 
         // Turn passes back to the shooter to fire again
-        state.turn = shooter;
-        env.storage().instance().set(&DataKey::GameState, &state);
+        pass_turn(&env, &mut state, shooter.clone());
+        check_move_limit(&env, &mut state);
+        check_out_of_targets(&env, &mut state, &shooter);
+        check_ammo(&env, &mut state, &shooter);
+        save_game(&env, &state);
     }
 
-    // Returns GameState directly — panics (→ simulation error) if not initialized.
-    // Frontend catches simulation errors and treats them as "no game yet".
-    pub fn get_state(env: Env) -> GameState {
-        env.storage()
-            .instance()
-            .get::<DataKey, GameState>(&DataKey::GameState)
-            .expect("No game")
-    }
+    /// Concede the pending shot as a hit instead of proving an answer. Only
+    /// with `allow_skip`; the cell is un-fired so the conceded hit is never
+    /// checked against the revealed board.
+    pub fn skip_response(env: Env, defender: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        defender.require_auth();
 
-    pub fn get_player_stats(env: Env, player: Address) -> Option<PlayerStats> {
-        env.storage()
+        let allow_skip = load_config(&env).map(|config| config.allow_skip).unwrap_or(false);
+        assert!(allow_skip, "Skipping not allowed");
+
+        let mut state = load_game(&env).expect("No game");
+
+        assert!(state.phase == GamePhase::Playing, "Not playing");
+        assert!(!is_coop(&state), "Can't skip in co-op");
+        assert!(state.pending_shot_x != NO_SHOT, "No pending shot");
+        assert!(defender != state.pending_shooter, "Shooter can't respond");
+        assert!(state.turn == defender, "Not your turn");
+
+        let shooter = state.pending_shooter.clone();
+        let (x, y) = (state.pending_shot_x, state.pending_shot_y);
+        unfire(&env, &mut state, &shooter, x, y);
+        state.pending_shot_x = NO_SHOT;
+        state.pending_shot_y = NO_SHOT;
+        bump(&mut state.move_number);
+        publish(&env, &state, "shot_conceded", (defender.clone(), x, y));
+
+        update_hit_streak(&env, &mut state, &shooter, true);
+        if !score_hit(&env, &mut state, &defender, shooter.clone(), 0, 0) {
+            pass_turn(&env, &mut state, shooter.clone());
+            check_ammo(&env, &mut state, &shooter);
+        }
+        save_game(&env, &state);
+    }
+
+    /// Pie rule: before anyone has fired, player2 may take the first turn
+    /// away from player1. Only possible once per game.
+    pub fn invoke_swap(env: Env, player2: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player2.require_auth();
+
+        let pie_rule = load_config(&env).map(|config| config.pie_rule).unwrap_or(false);
+        assert!(pie_rule, "Pie rule disabled");
+
+        let mut state = load_game(&env).expect("No game");
+
+        assert!(state.phase == GamePhase::Playing, "Not playing");
+        assert!(player2 == state.player2, "Only player2 can swap");
+        assert!(
+            state.turn == state.player1 && state.shots_fired_p1 == 0,
+            "Too late to swap"
+        );
+
+        pass_turn(&env, &mut state, player2.clone());
+        publish(&env, &state, "roles_swapped", player2);
+
+        save_game(&env, &state);
+    }
+
+    pub fn surrender(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let mut state = load_game(&env).expect("No game");
+
+        assert!(
+            matches!(state.phase, GamePhase::Commit | GamePhase::Playing | GamePhase::Paused),
+            "Game not in progress"
+        );
+        assert!(player == state.player1 || player == state.player2, "Not a player");
+
+        // Giving up a co-op game loses it for the team
+        if is_coop(&state) {
+            coop::end(&env, &mut state, false);
+            save_game(&env, &state);
+            return;
+        }
+        let winner = other_player(&state, &player);
+        forfeit_game(&env, &mut state, winner);
+        save_game(&env, &state);
+    }
+
+    /// Any game still in storage by session id — the current one, or a
+    /// finished one that has since been reset away.
+    pub fn get_game(env: Env, game_id: u32) -> Option<GameState> {
+        env.storage().persistent().get::<DataKey, GameState>(&DataKey::Game(game_id))
+    }
+
+    // Returns GameState directly — panics (→ simulation error) if not initialized.
+    // Frontend catches simulation errors and treats them as "no game yet".
+    // Until p2_joined, player2 is only a placeholder (a copy of player1); use
+    // get_lobby — or check p2_joined — before reading it.
+    pub fn get_state(env: Env) -> GameState {
+        load_game(&env).expect("No game")
+    }
+
+    /// sha256 of the GameState's XDR encoding, which is deterministic, so a
+    /// light client can check its copy of the state without fetching it.
+    pub fn get_state_hash(env: Env) -> BytesN<32> {
+        let state = load_game(&env).expect("No game");
+        env.crypto().sha256(&state.to_xdr(&env)).into()
+    }
+
+    pub fn get_lobby(env: Env) -> Lobby {
+        let state = load_game(&env).expect("No game");
+        Lobby {
+            phase: state.phase,
+            player1: state.player1,
+            player2: if state.p2_joined { Some(state.player2) } else { None },
+        }
+    }
+
+    // (p1_committed, p2_committed) — lets clients poll the Commit phase cheaply
+    pub fn get_commit_status(env: Env) -> (bool, bool) {
+        let state = load_game(&env).expect("No game");
+        (state.p1_committed, state.p2_committed)
+    }
+
+    // Who has to answer the pending shot, if any. The turn passes to them
+    // as the shot is fired — in co-op, to whichever teammate is up.
+    pub fn get_pending_defender(env: Env) -> Option<Address> {
+        let state = load_game(&env).expect("No game");
+        if state.pending_shot_x == NO_SHOT {
+            return None;
+        }
+        Some(state.turn)
+    }
+
+    // Whether fire_shot(shooter, x, y) would go through right now
+    pub fn can_fire(env: Env, shooter: Address, x: u32, y: u32) -> bool {
+        let state = load_game(&env).expect("No game");
+        validate_shot(&env, &state, &shooter, 0, x, y).is_ok()
+    }
+
+    // `player`'s board commitment, None until they've committed
+    pub fn get_board_hash(env: Env, player: Address) -> Option<Vec<BytesN<32>>> {
+        let state = load_game(&env).expect("No game");
+        let (committed, board_hash) = if player == state.player1 {
+            (state.p1_committed, state.board_hash_p1)
+        } else if player == state.player2 {
+            (state.p2_committed, state.board_hash_p2)
+        } else {
+            panic!("Not a player");
+        };
+        committed.then_some(board_hash)
+    }
+
+    // Joined players, player1 first
+    pub fn get_players(env: Env) -> Vec<Address> {
+        let state = load_game(&env).expect("No game");
+        let mut players = Vec::new(&env);
+        if state.p1_joined {
+            players.push_back(state.player1);
+        }
+        if state.p2_joined {
+            players.push_back(state.player2);
+        }
+        players
+    }
+
+    // Whether `player` is the one expected to act right now — fire, or answer
+    // the pending shot
+    pub fn is_my_turn(env: Env, player: Address) -> bool {
+        let state = load_game(&env).expect("No game");
+        state.phase == GamePhase::Playing && state.turn == player
+    }
+
+    // Bitmask of cells `attacker` may still fire at — bit (y * BOARD_SIZE + x).
+    // In a fleet game this is grid 0, the one fire_shot aims at.
+    pub fn get_available_targets(env: Env, attacker: Address) -> u32 {
+        Self::get_available_targets_at_board(env, attacker, 0)
+    }
+
+    pub fn was_fired(env: Env, attacker: Address, x: u32, y: u32) -> bool {
+        Self::was_fired_at_board(env, attacker, 0, x, y)
+    }
+
+    // Total escrowed for the current game; 0 for unwagered games and after payout
+    pub fn get_pot(env: Env) -> i128 {
+        let state = load_game(&env).expect("No game");
+        state.pot
+    }
+
+    /// What the contract actually holds of `token` — lets anyone check the
+    /// escrow against open pots and the match queue.
+    pub fn get_contract_balance(env: Env, token: Address) -> i128 {
+        token::Client::new(&env, &token).balance(&env.current_contract_address())
+    }
+
+    pub fn is_finished(env: Env) -> bool {
+        load_game(&env).is_some_and(|state| state.phase == GamePhase::Finished)
+    }
+
+    pub fn get_game_mode(env: Env) -> GameMode {
+        load_game(&env).expect("No game").mode
+    }
+
+    // Shots fired by both players combined
+    pub fn get_total_shots(env: Env) -> u32 {
+        let state = load_game(&env).expect("No game");
+        state.shots_fired_p1.checked_add(state.shots_fired_p2).expect("COUNTER_OVERFLOW")
+    }
+
+    // (hits_on_p1, hits_on_p2)
+    pub fn get_hits(env: Env) -> (u32, u32) {
+        let state = load_game(&env).expect("No game");
+        (state.hits_on_p1, state.hits_on_p2)
+    }
+
+    // Side length of the grid. Fixed at BOARD_SIZE for every game: shot and
+    // hit masks are u32, which a larger grid wouldn't fit.
+    pub fn get_board_size(_env: Env) -> u32 {
+        BOARD_SIZE
+    }
+
+    // Completed fire/respond exchanges so far
+    pub fn get_move_number(env: Env) -> u32 {
+        let state = load_game(&env).expect("No game");
+        state.move_number
+    }
+
+    // Seconds until the current turn deadline; 0 once it has passed or when
+    // the deployment has no turn timeout
+    pub fn get_remaining_turn_time(env: Env) -> u64 {
+        let state = load_game(&env).expect("No game");
+        state.turn_deadline.saturating_sub(env.ledger().timestamp())
+    }
+
+    // Timestamp the current turn must be played by, for client reminders;
+    // None outside Playing or when the deployment has no turn timeout
+    pub fn get_deadline(env: Env) -> Option<u64> {
+        load_game(&env)
+            .filter(|state| state.phase == GamePhase::Playing && state.turn_deadline > 0)
+            .map(|state| state.turn_deadline)
+    }
+
+    // When the Commit phase's `commit_timeout` runs out; None outside Commit
+    // or without a timeout
+    pub fn get_commit_deadline(env: Env) -> Option<u64> {
+        let timeout = load_config(&env).map(|config| config.commit_timeout).unwrap_or(0);
+        load_game(&env)
+            .filter(|state| state.phase == GamePhase::Commit && timeout > 0)
+            .map(|_| load_timeline(&env).commit_started_at.saturating_add(timeout))
+    }
+
+    pub fn get_player_stats(env: Env, player: Address) -> Option<PlayerStats> {
+        env.storage()
             .persistent()
             .get::<DataKey, PlayerStats>(&DataKey::PlayerStats(player))
     }
+
+    pub fn get_player_losses(env: Env, player: Address) -> u32 {
+        load_stats(&env, &player).games_lost
+    }
+
+    /// games_won / games_played in basis points; 0 for a player with no games.
+    pub fn get_player_win_rate(env: Env, player: Address) -> u32 {
+        let stats = load_stats(&env, &player);
+        if stats.games_played == 0 {
+            return 0;
+        }
+        (stats.games_won as u64 * 10_000 / stats.games_played as u64) as u32
+    }
+
+    pub fn get_forfeit_count(env: Env, player: Address) -> u32 {
+        load_stats(&env, &player).forfeits
+    }
+
+    /// Stats for player1 and player2 of the current game, in that order.
+    pub fn get_game_player_stats(env: Env) -> (Option<PlayerStats>, Option<PlayerStats>) {
+        let state = load_game(&env).expect("No game");
+        (
+            Self::get_player_stats(env.clone(), state.player1),
+            Self::get_player_stats(env, state.player2),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, Env};
+
+    // Initialized with test_config
+    pub(crate) fn setup() -> (Env, Address, Address, BattleshipContractClient<'static>) {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        (env, p1, p2, client)
+    }
 
-    fn setup() -> (Env, Address, Address, BattleshipContractClient<'static>) {
+    // For tests that initialize with their own Config
+    pub(crate) fn setup_uninitialized() -> (Env, Address, Address, BattleshipContractClient<'static>) {
         let env = Env::default();
         env.mock_all_auths();
-        let id = env.register_contract(None, BattleshipContract);
+        let id = env.register(BattleshipContract, ());
         let client = BattleshipContractClient::new(&env, &id);
         let p1 = Address::generate(&env);
         let p2 = Address::generate(&env);
         (env, p1, p2, client)
     }
 
-    fn hash(env: &Env, s: u8) -> BytesN<32> { BytesN::from_array(env, &[s; 32]) }
-    fn proof(env: &Env) -> Bytes { Bytes::from_slice(env, &[1u8; 64]) }
+    // Defaults matching an uninitialized contract
+    pub(crate) fn test_config(env: &Env) -> Config {
+        Config {
+            absence_window: 0,
+            admin: Address::generate(env),
+            allow_skip: false,
+            commit_timeout: 0,
+            dev_mode: false,
+            dispute_window: 0,
+            enable_hub: true,
+            forfeit_policy: ForfeitPolicy::WinnerTakesAll,
+            hasher: None,
+            hub: None,
+            lobby_deposit: 0,
+            lobby_ttl: 0,
+            max_invalid_proofs: 0,
+            max_moves: 0,
+            min_fire_delay: 0,
+            out_of_targets: OutOfTargets::Draw,
+            pause_on_verifier_error: false,
+            pie_rule: false,
+            private_shots: false,
+            proof_version: 0,
+            rake_bps: 0,
+            reveal_window: 0,
+            stake_asset: StakeAsset::Disabled,
+            start_grace_period: 0,
+            turn_timeout: 0,
+            verbose_events: true,
+            verifier: None,
+            win_rule: WinRule::HitsCount,
+        }
+    }
+
+    // Registers a SAC as the stake token and funds each player with 1000
+    pub(crate) fn setup_staking(
+        env: &Env,
+        client: &BattleshipContractClient,
+        players: &[&Address],
+    ) -> Address {
+        setup_staking_with_policy(env, client, players, ForfeitPolicy::WinnerTakesAll)
+    }
+
+    pub(crate) fn setup_staking_with_policy(
+        env: &Env,
+        client: &BattleshipContractClient,
+        players: &[&Address],
+        forfeit_policy: ForfeitPolicy,
+    ) -> Address {
+        let config = Config { forfeit_policy, ..test_config(env) };
+        setup_staking_with_config(env, client, players, config)
+    }
+
+    // As setup_staking, on top of `config`; the hasher is the mock
+    pub(crate) fn setup_staking_with_config(
+        env: &Env,
+        client: &BattleshipContractClient,
+        players: &[&Address],
+        config: Config,
+    ) -> Address {
+        let token = fund_players(env, players);
+        client.initialize(&Config {
+            hasher: Some(env.register(MockHasher, ())),
+            stake_asset: StakeAsset::Token(token.clone()),
+            ..config
+        });
+        token
+    }
+
+    // Registers a SAC, without initializing, and funds each player with 1000
+    pub(crate) fn fund_players(env: &Env, players: &[&Address]) -> Address {
+        let sac = env.register_stellar_asset_contract_v2(Address::generate(env));
+        let asset = token::StellarAssetClient::new(env, &sac.address());
+        for player in players {
+            asset.mint(player, &1000);
+        }
+        sac.address()
+    }
+
+    // A default lobby with both boards committed, P1 on move
+    pub(crate) fn start_game(
+        env: &Env,
+        client: &BattleshipContractClient,
+        p1: &Address,
+        p2: &Address,
+    ) {
+        client.join_game(p1);
+        client.join_game(p2);
+        client.commit_board(p1, &hash(env, 1));
+        client.commit_board(p2, &hash(env, 2));
+    }
+
+    // As start_game, in a lobby P1 opened with `options`
+    pub(crate) fn start_game_with(
+        env: &Env,
+        client: &BattleshipContractClient,
+        p1: &Address,
+        p2: &Address,
+        options: &GameOptions,
+    ) {
+        client.create_game(p1, options);
+        client.join_game(p2);
+        client.commit_board(p1, &hash(env, 1));
+        client.commit_board(p2, &hash(env, 2));
+    }
+
+    // Stand-in for the Poseidon2 hasher: knows the Prover.toml test vector and
+    // falls back to sha256 for anything else.
+    mod mock_hasher {
+        use crate::PRACTICE_BOARD_HASH;
+        use soroban_sdk::{contract, contractimpl, Bytes, BytesN, Env, Vec};
+
+        #[contract]
+        pub struct MockHasher;
+
+        #[contractimpl]
+        impl MockHasher {
+            pub fn hash_board(env: Env, cells: Vec<u32>, salt: BytesN<32>) -> BytesN<32> {
+                let mut prover_board = [0u32; 25];
+                for i in [0usize, 7, 24] {
+                    prover_board[i] = 1;
+                }
+                let mut prover_salt = [0u8; 32];
+                prover_salt[28..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+                if cells == Vec::from_array(&env, prover_board) && salt.to_array() == prover_salt {
+                    return BytesN::from_array(&env, &PRACTICE_BOARD_HASH);
+                }
+
+                let mut input = Bytes::new(&env);
+                for cell in cells.iter() {
+                    input.push_back(cell as u8);
+                }
+                input.append(&salt.into());
+                env.crypto().sha256(&input).into()
+            }
+        }
+    }
+
+    pub(crate) use mock_hasher::MockHasher;
+
+    pub(crate) fn hash(env: &Env, s: u8) -> BytesN<32> { BytesN::from_array(env, &[s; 32]) }
+    // Stand-in proof whose output field answers `is_hit`
+    pub(crate) fn proof(env: &Env, is_hit: bool) -> Bytes {
+        let mut bytes = [1u8; 64];
+        bytes[63] = is_hit as u8;
+        Bytes::from_slice(env, &bytes)
+    }
+    pub(crate) fn salt(env: &Env) -> BytesN<32> { BytesN::from_array(env, &[7u8; 32]) }
+    // The commitment to `board` under salt() that the configured hasher accepts
+    pub(crate) fn board_hash(client: &BattleshipContractClient, board: u32) -> BytesN<32> {
+        client.compute_board_hash(&board, &salt(&client.env))
+    }
 
     #[test]
     fn test_full_game() {
@@ -296,7 +1791,7 @@ mod tests {
         // P1 wins by sinking all 3 ships
         for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
             client.fire_shot(&p1, &coord.0, &coord.1);
-            client.submit_response(&p2, &coord.0, &coord.1, &true, &proof(&env));
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
         }
 
         let state = client.get_state();
@@ -307,13 +1802,10 @@ mod tests {
     #[test]
     fn test_miss_and_reset() {
         let (env, p1, p2, client) = setup();
-        client.join_game(&p1);
-        client.join_game(&p2);
-        client.commit_board(&p1, &hash(&env, 1));
-        client.commit_board(&p2, &hash(&env, 2));
+        start_game(&env, &client, &p1, &p2);
 
         client.fire_shot(&p1, &0, &0);
-        client.submit_response(&p2, &0, &0, &false, &proof(&env));
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
 
         let state = client.get_state();
         assert_eq!(state.hits_on_p2, 0);
@@ -322,4 +1814,1187 @@ mod tests {
 
         client.reset_game(&p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "RATE_LIMITED")]
+    fn test_join_twice_same_ledger() {
+        let (_env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        client.reset_game(&p1);
+        client.join_game(&p1);
+    }
+
+    #[test]
+    fn test_join_again_next_ledger() {
+        let (env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        client.reset_game(&p1);
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        assert_eq!(client.get_state().player1, p1);
+    }
+
+    #[test]
+    fn test_commit_status() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert_eq!(client.get_commit_status(), (false, false));
+
+        client.commit_board(&p1, &hash(&env, 1));
+        assert_eq!(client.get_commit_status(), (true, false));
+    }
+
+    #[test]
+    fn test_handicap_fleet_sizes() {
+        let (env, p1, p2, client) = setup();
+        let options =
+            GameOptions { ships_to_sink_p1: 3, ships_to_sink_p2: 5, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        let state = client.get_state();
+        assert_eq!((state.ships_to_sink_p1, state.ships_to_sink_p2), (3, 5));
+
+        // P2 has a 5-ship fleet — three hits are no longer enough
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+
+        for coord in [(3u32, 3u32), (4, 4)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+        assert_eq!(state.hits_on_p2, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game already exists")]
+    fn test_create_game_twice() {
+        let (_env, p1, p2, client) = setup();
+        client.create_game(&p1, &GameOptions::default());
+        client.create_game(&p2, &GameOptions::default());
+    }
+
+    #[test]
+    fn test_pending_defender() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        assert_eq!(client.get_pending_defender(), None);
+
+        client.fire_shot(&p1, &1, &2);
+        assert_eq!(client.get_pending_defender(), Some(p2.clone()));
+
+        client.submit_response(&p2, &1, &2, &proof(&env, false));
+        assert_eq!(client.get_pending_defender(), None);
+    }
+
+    #[test]
+    fn test_practice_game_skips_commit() {
+        let (env, p1, p2, client) = setup();
+        client.create_game(&p1, &GameOptions { mode: GameMode::Practice, ..Default::default() });
+        client.join_game(&p2);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.turn, p1);
+        let practice_hash = BytesN::from_array(&env, &PRACTICE_BOARD_HASH);
+        assert_eq!(state.board_hash_p2, Vec::from_array(&env, [practice_hash]));
+
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        assert_eq!(client.get_state().hits_on_p2, 1);
+    }
+
+    fn start_blitz(env: &Env, p1: &Address, p2: &Address, client: &BattleshipContractClient) {
+        let options = GameOptions { mode: GameMode::Blitz, time_budget: 60, ..Default::default() };
+        start_game_with(env, client, p1, p2, &options);
+    }
+
+    #[test]
+    fn test_cancel_shot_restores_clocks() {
+        let (env, p1, p2, client) = setup();
+        start_blitz(&env, &p1, &p2, &client);
+
+        env.ledger().with_mut(|l| l.timestamp += 20);
+        client.fire_shot(&p1, &0, &0);
+        env.ledger().with_mut(|l| l.timestamp += 10);
+        client.cancel_shot(&p1);
+        let state = client.get_state();
+        assert_eq!((state.remaining_time_p1, state.remaining_time_p2), (60, 60));
+
+        // The shooter's clock kept running through the cancelled shot
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_state().remaining_time_p1, 30);
+    }
+
+    #[test]
+    fn test_clock_exhausted_loses() {
+        let (env, p1, p2, client) = setup();
+        start_blitz(&env, &p1, &p2, &client);
+
+        env.ledger().with_mut(|l| l.timestamp += 30);
+        client.fire_shot(&p1, &0, &0);
+        env.ledger().with_mut(|l| l.timestamp += 5);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+
+        let state = client.get_state();
+        assert_eq!((state.remaining_time_p1, state.remaining_time_p2), (30, 55));
+
+        // P1 sits on the move past their remaining 30s
+        env.ledger().with_mut(|l| l.timestamp += 30);
+        client.claim_time_win(&p2);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Clock not expired")]
+    fn test_claim_time_win_too_early() {
+        let (env, p1, p2, client) = setup();
+        start_blitz(&env, &p1, &p2, &client);
+
+        env.ledger().with_mut(|l| l.timestamp += 59);
+        client.claim_time_win(&p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of time")]
+    fn test_move_after_clock_expired() {
+        let (env, p1, p2, client) = setup();
+        start_blitz(&env, &p1, &p2, &client);
+
+        env.ledger().with_mut(|l| l.timestamp += 60);
+        client.fire_shot(&p1, &0, &0);
+    }
+
+    #[test]
+    fn test_available_targets() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        let all = (1u32 << 25) - 1;
+        assert_eq!(client.get_available_targets(&p1), all);
+
+        for coord in [(0u32, 0u32), (4, 0), (2, 3)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, false));
+        }
+
+        let expected = all & !(1 << 0) & !(1 << 4) & !(1 << 17);
+        assert_eq!(client.get_available_targets(&p1), expected);
+        assert_eq!(client.get_available_targets(&p2), all);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already fired")]
+    fn test_fire_same_cell_twice() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &1, &1);
+        client.submit_response(&p2, &1, &1, &proof(&env, false));
+        client.fire_shot(&p1, &1, &1);
+    }
+
+    #[test]
+    fn test_fire_shot_retry_with_same_nonce() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot_with_nonce(&p1, &1, &1, &7);
+        client.fire_shot_with_nonce(&p1, &1, &1, &7);
+        let state = client.get_state();
+        assert_eq!(state.shots_fired_p1, 1);
+        assert_eq!(state.turn, p2);
+
+        // A fresh nonce is a real shot, and it's not p1's turn
+        assert!(client.try_fire_shot_with_nonce(&p1, &2, &2, &8).is_err());
+    }
+
+    #[test]
+    fn test_derive_game_id() {
+        let (env, p1, p2, client) = setup();
+        let id = client.derive_game_id(&p1, &100, &5);
+        assert_eq!(client.derive_game_id(&p1, &100, &5), id);
+        assert_ne!(client.derive_game_id(&p2, &100, &5), id);
+        assert_ne!(client.derive_game_id(&p1, &101, &5), id);
+        assert_ne!(client.derive_game_id(&p1, &100, &6), id);
+
+        let ledger = env.ledger().get();
+        let expected = client.derive_game_id(&p1, &ledger.timestamp, &ledger.sequence_number);
+        client.join_game(&p1);
+        assert_eq!(client.get_state().session_id, expected);
+    }
+
+    #[test]
+    fn test_games_in_one_ledger_get_distinct_ids() {
+        let (env, p1, p2, client) = setup();
+        let (first, second) = env.as_contract(&client.address, || {
+            let first = new_game(&env, &p1, &GameOptions::default()).session_id;
+            (first, new_game(&env, &p1, &GameOptions::default()).session_id)
+        });
+        assert_ne!(first, second);
+
+        // A lobby reset and reopened through matchmaking within the ledger
+        client.join_game(&p1);
+        let lobby = client.get_state().session_id;
+        client.reset_game(&p1);
+        client.enqueue(&p2, &0);
+        client.enqueue(&Address::generate(&env), &0);
+        let rematched = client.get_state().session_id;
+        assert!(![first, second, lobby].contains(&rematched));
+        assert_ne!(lobby, first);
+    }
+
+    #[test]
+    fn test_skip_response_concedes_a_hit() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { allow_skip: true, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &1, &1);
+        client.skip_response(&p2);
+
+        let state = client.get_state();
+        assert_eq!(state.hits_on_p2, 1);
+        assert_eq!(state.hit_mask_p2, 0);
+        assert_eq!(state.fired_mask_p1, 0);
+        assert_eq!(state.turn, p1);
+        assert_eq!(client.get_pending_defender(), None);
+
+        // Conceding the last ship ends the game like a proven hit
+        for x in [2u32, 3] {
+            client.fire_shot(&p1, &x, &0);
+            client.skip_response(&p2);
+        }
+        assert_eq!(client.get_state().winner, p1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Skipping not allowed")]
+    fn test_skip_response_disabled() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &1, &1);
+        client.skip_response(&p2);
+    }
+
+    #[test]
+    fn test_pie_rule_swap() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { pie_rule: true, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+
+        client.invoke_swap(&p2);
+        assert_eq!(client.get_state().turn, p2);
+        assert!(client.try_fire_shot(&p1, &0, &0).is_err());
+
+        client.fire_shot(&p2, &0, &0);
+        client.submit_response(&p1, &0, &0, &proof(&env, false));
+        assert_eq!(client.get_state().turn, p2);
+        assert!(client.try_invoke_swap(&p2).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Too late to swap")]
+    fn test_pie_rule_after_first_shot() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { pie_rule: true, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        client.invoke_swap(&p2);
+    }
+
+    #[test]
+    fn test_games_stored_per_session() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.surrender(&p2);
+        let first = client.get_state().session_id;
+        client.reset_game(&p1);
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        let second = client.get_state().session_id;
+
+        assert_ne!(first, second);
+        let old = client.get_game(&first).unwrap();
+        assert_eq!((old.phase, old.winner), (GamePhase::Finished, p1));
+        assert_eq!(client.get_game(&second).unwrap().phase, GamePhase::WaitingForPlayers);
+
+        // An abandoned game doesn't linger
+        client.reset_game(&p2);
+        assert!(client.get_game(&second).is_none());
+    }
+
+    #[test]
+    fn test_game_ttl_extended_on_save() {
+        use soroban_sdk::testutils::storage::Persistent as _;
+
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        let key = DataKey::Game(client.get_state().session_id);
+        let ttl = || env.as_contract(&client.address, || env.storage().persistent().get_ttl(&key));
+        assert!(ttl() >= GAME_TTL_THRESHOLD);
+
+        // Each move pushes expiry out again, so a slow game survives
+        env.ledger().with_mut(|l| l.sequence_number += GAME_TTL_EXTEND_TO - 10);
+        client.join_game(&p2);
+        env.ledger().with_mut(|l| l.sequence_number += GAME_TTL_EXTEND_TO - 10);
+        assert_eq!(client.get_state().phase, GamePhase::Commit);
+    }
+
+    #[test]
+    fn test_response_rechecks_pending_bounds() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        // An in-bounds shot answers as usual
+        client.fire_shot(&p1, &4, &4);
+        client.submit_response(&p2, &4, &4, &proof(&env, true));
+        assert_eq!(client.get_state().hits_on_p2, 1);
+
+        // A pending shot that somehow left the grid is refused
+        client.fire_shot(&p1, &0, &0);
+        env.as_contract(&client.address, || {
+            let mut state = load_game(&env).unwrap();
+            state.pending_shot_x = BOARD_SIZE;
+            save_game(&env, &state);
+        });
+        let result = client.try_submit_response(&p2, &BOARD_SIZE, &0, &proof(&env, true));
+        assert!(result.is_err());
+        assert_eq!(client.get_state().hits_on_p2, 1);
+    }
+
+    #[test]
+    fn test_validate_shot_failure_modes() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let outsider = Address::generate(&env);
+        let check = |state: &GameState, shooter: &Address, x, y| {
+            env.as_contract(&client.address, || validate_shot(&env, state, shooter, 0, x, y))
+        };
+        assert_eq!(check(&client.get_state(), &p1, 0, 0), Err("Not playing"));
+
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+        let state = client.get_state();
+        assert_eq!(check(&state, &p1, BOARD_SIZE, 0), Err("x out of range"));
+        assert_eq!(check(&state, &p1, 0, BOARD_SIZE), Err("y out of range"));
+        assert_eq!(check(&state, &outsider, 0, 0), Err("NOT_A_PLAYER"));
+        assert_eq!(check(&state, &p2, 0, 0), Err("Not your turn"));
+        assert_eq!(check(&state, &p1, 0, 0), Ok(()));
+
+        client.fire_shot(&p1, &0, &0);
+        let mut state = client.get_state();
+        state.turn = p1.clone();
+        assert_eq!(check(&state, &p1, 1, 1), Err("Shot pending"));
+
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        assert_eq!(check(&client.get_state(), &p1, 0, 0), Err("Already fired"));
+        assert!(!client.can_fire(&p1, &0, &0));
+        assert!(client.can_fire(&p1, &1, &0));
+    }
+
+    #[test]
+    fn test_get_board_hash() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert_eq!(client.get_board_hash(&p1), None);
+        assert_eq!(client.get_board_hash(&p2), None);
+
+        client.commit_board(&p1, &hash(&env, 1));
+        assert_eq!(client.get_board_hash(&p1), Some(Vec::from_array(&env, [hash(&env, 1)])));
+        assert_eq!(client.get_board_hash(&p2), None);
+
+        client.commit_board(&p2, &hash(&env, 2));
+        assert_eq!(client.get_board_hash(&p2), Some(Vec::from_array(&env, [hash(&env, 2)])));
+    }
+
+    #[test]
+    fn test_get_players() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        assert_eq!(client.get_players(), Vec::from_array(&env, [p1.clone()]));
+        client.join_game(&p2);
+        assert_eq!(client.get_players(), Vec::from_array(&env, [p1, p2]));
+    }
+
+    #[test]
+    fn test_is_my_turn() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert!(!client.is_my_turn(&p1));
+
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+        assert!(client.is_my_turn(&p1));
+        assert!(!client.is_my_turn(&p2));
+
+        // Pending shot: the defender must answer
+        client.fire_shot(&p1, &0, &0);
+        assert!(!client.is_my_turn(&p1));
+        assert!(client.is_my_turn(&p2));
+    }
+
+    #[test]
+    #[should_panic(expected = "COUNTER_OVERFLOW")]
+    fn test_shot_counter_overflow() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        // Pathological game that already sits at the limit
+        env.as_contract(&client.address, || {
+            let mut state = load_game(&env).unwrap();
+            state.shots_fired_p1 = u32::MAX;
+            save_game(&env, &state);
+        });
+        client.fire_shot(&p1, &0, &0);
+    }
+
+    #[test]
+    fn test_bump_near_limit() {
+        let mut counter = u32::MAX - 1;
+        bump(&mut counter);
+        assert_eq!(counter, u32::MAX);
+    }
+
+    #[test]
+    fn test_cancel_shot() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &1, &1);
+        client.cancel_shot(&p1);
+
+        let state = client.get_state();
+        assert_eq!(state.turn, p1);
+        assert_eq!(state.shots_fired_p1, 0);
+        assert_eq!(state.fired_mask_p1, 0);
+        assert_eq!(client.get_pending_defender(), None);
+
+        // The cell is free again
+        client.fire_shot(&p1, &1, &1);
+        assert!(client.try_cancel_shot(&p2).is_err());
+        client.submit_response(&p2, &1, &1, &proof(&env, false));
+        assert!(client.try_cancel_shot(&p1).is_err());
+    }
+
+    #[test]
+    fn test_cancel_shot_keeps_deadline() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start_with_timeout(&env, &client, &p1, &p2);
+        let deadline = client.get_state().turn_deadline;
+
+        env.ledger().with_mut(|l| l.timestamp += 90);
+        client.fire_shot(&p1, &1, &1);
+        client.cancel_shot(&p1);
+        let state = client.get_state();
+        assert_eq!(state.turn_deadline, deadline);
+        assert_eq!(state.last_move_at, 0);
+
+        // Once per turn
+        client.fire_shot(&p1, &1, &1);
+        assert!(client.try_cancel_shot(&p1).is_err());
+        client.submit_response(&p2, &1, &1, &proof(&env, false));
+        client.fire_shot(&p1, &0, &0);
+        client.cancel_shot(&p1);
+
+        env.ledger().with_mut(|l| l.timestamp += 301);
+        client.claim_timeout(&p2);
+        assert_eq!(client.get_state().winner, p2);
+    }
+
+    #[test]
+    fn test_best_hit_streak() {
+        let (env, p1, p2, client) = setup();
+        let options = GameOptions { ships_to_sink_p2: 5, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        // hit, hit, miss, hit — best streak is 2
+        for (x, is_hit) in [(0u32, true), (1, true), (2, false), (3, true)] {
+            client.fire_shot(&p1, &x, &0);
+            client.submit_response(&p2, &x, &0, &proof(&env, is_hit));
+        }
+        assert_eq!(client.get_state().hit_streak_p1, 1);
+        assert_eq!(client.get_player_stats(&p1).unwrap().best_hit_streak, 2);
+
+        // three more in a row beats it (and wins the game)
+        for x in [0u32, 1] {
+            client.fire_shot(&p1, &x, &1);
+            client.submit_response(&p2, &x, &1, &proof(&env, true));
+        }
+        let stats = client.get_player_stats(&p1).unwrap();
+        assert_eq!(stats.best_hit_streak, 3);
+        assert_eq!(stats.games_won, 1);
+    }
+
+    #[test]
+    fn test_game_player_stats() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        client.surrender(&p2);
+
+        let (stats1, stats2) = client.get_game_player_stats();
+        let (stats1, stats2) = (stats1.unwrap(), stats2.unwrap());
+        assert_eq!((stats1.games_played, stats1.games_won), (1, 1));
+        assert_eq!((stats2.games_played, stats2.games_won), (1, 0));
+        assert_eq!(client.get_forfeit_count(&p2), 1);
+        assert_eq!(client.get_player_losses(&p1), 0);
+        assert_eq!(client.get_player_losses(&p2), 1);
+    }
+
+    #[test]
+    fn test_player_win_rate() {
+        let (env, p1, p2, client) = setup();
+        assert_eq!(client.get_player_win_rate(&p1), 0);
+
+        // p1 wins two, loses one
+        for loser in [&p2, &p2, &p1] {
+            env.ledger().with_mut(|l| l.sequence_number += 1);
+            client.join_game(&p1);
+            client.join_game(&p2);
+            client.surrender(loser);
+            client.reset_game(&p1);
+        }
+        assert_eq!(client.get_player_win_rate(&p1), 6666);
+        assert_eq!(client.get_player_win_rate(&p2), 3333);
+        assert_eq!(client.get_player_win_rate(&Address::generate(&env)), 0);
+    }
+
+    #[test]
+    fn test_lobby_with_one_player() {
+        let (_env, p1, p2, client) = setup();
+        client.join_game(&p1);
+
+        let state = client.get_state();
+        assert!(state.p1_joined);
+        assert!(!state.p2_joined);
+        assert_eq!(state.phase, GamePhase::WaitingForPlayers);
+
+        let lobby = client.get_lobby();
+        assert_eq!(lobby.player1, p1);
+        assert_eq!(lobby.player2, None);
+
+        client.join_game(&p2);
+        assert_eq!(client.get_lobby().player2, Some(p2));
+    }
+
+    #[test]
+    fn test_wagered_game_pays_winner() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        assert_eq!(client.get_state().pot, 200);
+        assert_eq!(token.balance(&p1), 900);
+
+        let board_p2 = (1 << 0) | (1 << 6) | (1 << 12);
+        client.commit_board(&p1, &board_hash(&client, 0b111));
+        client.commit_board(&p2, &board_hash(&client, board_p2));
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+
+        client.reveal_board(&p1, &0b111, &salt(&env));
+        client.reveal_board(&p2, &board_p2, &salt(&env));
+        client.claim_winnings(&p1);
+
+        assert_eq!(token.balance(&p1), 1100);
+        assert_eq!(token.balance(&p2), 900);
+        assert_eq!(client.get_state().pot, 0);
+    }
+
+    // Native XLM has no admin to mint with, so balances are written straight
+    // into the SAC's storage, in the layout the host uses
+    mod native_xlm {
+        use soroban_sdk::{contracttype, Address, Env};
+
+        #[contracttype]
+        enum SacKey {
+            Balance(Address),
+        }
+
+        #[contracttype]
+        struct BalanceValue {
+            amount: i128,
+            authorized: bool,
+            clawback: bool,
+        }
+
+        pub fn fund(env: &Env, sac: &Address, holder: &Address, amount: i128) {
+            let balance = BalanceValue { amount, authorized: true, clawback: false };
+            env.as_contract(sac, || {
+                env.storage().persistent().set(&SacKey::Balance(holder.clone()), &balance);
+            });
+        }
+    }
+
+    #[test]
+    fn test_native_wagered_game() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let native = env.deployer()
+            .with_stellar_asset(Bytes::from_array(&env, &NATIVE_ASSET_XDR))
+            .deploy();
+        assert_eq!(native, native_asset(&env));
+        native_xlm::fund(&env, &native, &p1, 1000);
+        native_xlm::fund(&env, &native, &p2, 1000);
+        client.initialize(&Config {
+            hasher: Some(env.register(MockHasher, ())),
+            stake_asset: StakeAsset::Native,
+            ..test_config(&env)
+        });
+        let xlm = token::Client::new(&env, &native);
+
+        client.create_game(&p1, &GameOptions { stake: 100, ships_to_sink_p2: 1, ..Default::default() });
+        client.join_game(&p2);
+        assert_eq!(xlm.balance(&client.address), 200);
+
+        client.commit_board(&p1, &board_hash(&client, 0b111));
+        client.commit_board(&p2, &board_hash(&client, 0b1));
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        client.reveal_board(&p1, &0b111, &salt(&env));
+        client.reveal_board(&p2, &0b1, &salt(&env));
+        client.claim_winnings(&p1);
+
+        assert_eq!(xlm.balance(&p1), 1100);
+        assert_eq!(xlm.balance(&p2), 900);
+    }
+
+    #[test]
+    fn test_reset_refunds_unfinished_wager() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        client.reset_game(&p1);
+
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+    }
+
+    #[test]
+    fn test_reset_wagered_game_in_play_admin_only() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        start_game_with(&env, &client, &p1, &p2, &GameOptions { stake: 100, ..Default::default() });
+
+        assert!(client.try_reset_game(&p1).is_err());
+        assert!(client.try_reset_game(&p2).is_err());
+        assert_eq!(client.get_pot(), 200);
+
+        client.reset_game(&client.get_config().unwrap().admin);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+    }
+
+    #[test]
+    fn test_get_pot() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        setup_staking(&env, &client, &[&p1, &p2]);
+
+        client.create_game(&p1, &GameOptions { stake: 250, ..Default::default() });
+        assert_eq!(client.get_pot(), 250);
+        client.join_game(&p2);
+        assert_eq!(client.get_pot(), 500);
+
+        client.reset_game(&p1);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        assert_eq!(client.get_pot(), 0);
+    }
+
+    #[test]
+    fn test_contract_balance_matches_pot() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = setup_staking(&env, &client, &[&p1, &p2]);
+        assert_eq!(client.get_contract_balance(&token), 0);
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        assert_eq!(client.get_contract_balance(&token), client.get_pot());
+        assert_eq!(client.get_contract_balance(&token), 200);
+    }
+
+    fn start_wagered(p1: &Address, p2: &Address, client: &BattleshipContractClient) {
+        client.create_game(p1, &GameOptions {
+            mode: GameMode::Blitz,
+            stake: 100,
+            time_budget: 60,
+            ..Default::default()
+        });
+        client.join_game(p2);
+        client.commit_board(p1, &board_hash(client, 0b111));
+        client.commit_board(p2, &board_hash(client, 0b111));
+    }
+
+    #[test]
+    fn test_surrender_winner_takes_all() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        start_wagered(&p1, &p2, &client);
+
+        client.surrender(&p2);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+
+        client.reveal_board(&p1, &0b111, &salt(&env));
+        client.reveal_board(&p2, &0b111, &salt(&env));
+        client.claim_winnings(&p1);
+        assert_eq!(token.balance(&p1), 1100);
+        assert_eq!(token.balance(&p2), 900);
+    }
+
+    #[test]
+    fn test_surrender_refund_on_forfeit() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let policy = ForfeitPolicy::RefundOnForfeit;
+        let token = setup_staking_with_policy(&env, &client, &[&p1, &p2], policy);
+        let token = token::Client::new(&env, &token);
+        start_wagered(&p1, &p2, &client);
+
+        client.surrender(&p2);
+
+        assert_eq!(client.get_state().winner, p1);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+        assert_eq!(client.get_player_stats(&p1).unwrap().games_won, 1);
+    }
+
+    #[test]
+    fn test_failed_refund_leaves_no_partial_result() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let policy = ForfeitPolicy::RefundOnForfeit;
+        let token = setup_staking_with_policy(&env, &client, &[&p1, &p2], policy);
+        let token = token::Client::new(&env, &token);
+        start_wagered(&p1, &p2, &client);
+
+        // Escrow drained behind the contract's back — the refund can't succeed
+        let sink = Address::generate(&env);
+        env.as_contract(&client.address, || token.transfer(&client.address, &sink, &200));
+        assert!(client.try_surrender(&p2).is_err());
+
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+        assert!(client.get_player_stats(&p1).is_none());
+        assert!(client.get_player_stats(&p2).is_none());
+        assert!(client.get_finished_games(&p1).is_empty());
+    }
+
+    #[test]
+    fn test_timeout_refund_on_forfeit() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let policy = ForfeitPolicy::RefundOnForfeit;
+        let token = setup_staking_with_policy(&env, &client, &[&p1, &p2], policy);
+        let token = token::Client::new(&env, &token);
+        start_wagered(&p1, &p2, &client);
+
+        env.ledger().with_mut(|l| l.timestamp += 60);
+        client.claim_time_win(&p2);
+
+        assert_eq!(client.get_state().winner, p2);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+    }
+
+    #[test]
+    fn test_modes_play_differently() {
+        let (env, p1, p2, client) = setup();
+
+        // Classic: commit phase, no clock
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert_eq!(client.get_game_mode(), GameMode::Classic);
+        assert_eq!(client.get_state().phase, GamePhase::Commit);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+        env.ledger().with_mut(|l| l.timestamp += 30);
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_state().remaining_time_p1, 0);
+        assert!(client.try_claim_time_win(&p2).is_err());
+        client.reset_game(&p1);
+
+        // Practice: straight to Playing
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.create_game(&p1, &GameOptions { mode: GameMode::Practice, ..Default::default() });
+        client.join_game(&p2);
+        assert_eq!(client.get_game_mode(), GameMode::Practice);
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+        client.reset_game(&p1);
+
+        // Blitz: the shooter's clock is charged
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        start_blitz(&env, &p1, &p2, &client);
+        assert_eq!(client.get_game_mode(), GameMode::Blitz);
+        env.ledger().with_mut(|l| l.timestamp += 30);
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_state().remaining_time_p1, 30);
+    }
+
+    #[test]
+    #[should_panic(expected = "Blitz needs a time budget")]
+    fn test_blitz_requires_budget() {
+        let (_env, p1, _p2, client) = setup();
+        client.create_game(&p1, &GameOptions { mode: GameMode::Blitz, ..Default::default() });
+    }
+
+    #[test]
+    fn test_get_hits() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        client.fire_shot(&p1, &1, &0);
+        client.submit_response(&p2, &1, &0, &proof(&env, false));
+        client.fire_shot(&p1, &2, &0);
+        client.submit_response(&p2, &2, &0, &proof(&env, true));
+        assert_eq!(client.get_hits(), (0, 2));
+    }
+
+    #[test]
+    fn test_min_fire_delay() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { min_fire_delay: 3, ..test_config(&env) });
+        env.ledger().with_mut(|l| l.sequence_number = 10);
+        start_game(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.sequence_number = 12);
+        assert!(!client.can_fire(&p1, &0, &0));
+        assert!(client.try_fire_shot(&p1, &0, &0).is_err());
+
+        env.ledger().with_mut(|l| l.sequence_number = 13);
+        assert!(client.can_fire(&p1, &0, &0));
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_state().pending_shot_x, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Too early to fire")]
+    fn test_fire_before_delay() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { min_fire_delay: 1, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+    }
+
+    #[test]
+    fn test_is_finished() {
+        let (env, p1, p2, client) = setup();
+        assert!(!client.is_finished());
+        let options = GameOptions { ships_to_sink_p2: 1, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+        client.fire_shot(&p1, &0, &0);
+        assert!(!client.is_finished());
+
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        assert!(client.is_finished());
+    }
+
+    #[test]
+    fn test_total_shots() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        assert_eq!(client.get_total_shots(), 0);
+
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        client.fire_shot(&p1, &1, &0);
+        client.submit_response(&p2, &1, &0, &proof(&env, false));
+        client.fire_shot(&p1, &2, &0);
+        assert_eq!(client.get_total_shots(), 3);
+    }
+
+    #[test]
+    fn test_board_size() {
+        let (_env, p1, _p2, client) = setup();
+        assert_eq!(client.get_board_size(), 5);
+        client.join_game(&p1);
+        assert_eq!(client.get_board_size(), 5);
+    }
+
+    #[test]
+    fn test_move_number() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        assert_eq!(client.get_move_number(), 0);
+
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_move_number(), 0);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        assert_eq!(client.get_move_number(), 1);
+
+        client.fire_shot(&p1, &1, &0);
+        client.submit_response(&p2, &1, &0, &proof(&env, true));
+        assert_eq!(client.get_move_number(), 2);
+    }
+
+    // Every cell fired, all misses: nothing left to shoot at and no winner
+    fn fire_everywhere(out_of_targets: OutOfTargets) -> GameState {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { out_of_targets, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+
+        for i in 0..BOARD_SIZE * BOARD_SIZE {
+            let (x, y) = (i % BOARD_SIZE, i / BOARD_SIZE);
+            client.fire_shot(&p1, &x, &y);
+            client.submit_response(&p2, &x, &y, &proof(&env, false));
+        }
+        assert_eq!(client.get_available_targets(&p1), 0);
+        client.get_state()
+    }
+
+    #[test]
+    fn test_out_of_targets_draw() {
+        let state = fire_everywhere(OutOfTargets::Draw);
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(!state.has_winner);
+    }
+
+    #[test]
+    fn test_out_of_targets_loss() {
+        let state = fire_everywhere(OutOfTargets::Loss);
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert!(state.has_winner);
+        assert_eq!(state.winner, state.player2);
+    }
+
+    // Two 2-cell ships each: HitsCount ends after 2 hits, DistinctShipsSunk after 4
+    fn hits_until_win(win_rule: WinRule) -> u32 {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { win_rule, ..test_config(&env) });
+        let options = GameOptions {
+            ship_cells: 2,
+            ships_to_sink_p1: 2,
+            ships_to_sink_p2: 2,
+            ..Default::default()
+        };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        let mut x = 0;
+        while client.get_state().phase == GamePhase::Playing {
+            client.fire_shot(&p1, &x, &0);
+            client.submit_response(&p2, &x, &0, &proof(&env, true));
+            x += 1;
+        }
+        assert_eq!(client.get_state().winner, p1);
+        x
+    }
+
+    #[test]
+    fn test_win_rule_hits_count() {
+        assert_eq!(hits_until_win(WinRule::HitsCount), 2);
+    }
+
+    #[test]
+    fn test_win_rule_distinct_ships_sunk() {
+        assert_eq!(hits_until_win(WinRule::DistinctShipsSunk), 4);
+    }
+
+    #[test]
+    fn test_win_rule_first_to_hits() {
+        assert_eq!(hits_until_win(WinRule::FirstToHits(1)), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid win rule")]
+    fn test_win_rule_first_to_zero_hits() {
+        let (env, _p1, _p2, client) = setup_uninitialized();
+        client.initialize(&Config { win_rule: WinRule::FirstToHits(0), ..test_config(&env) });
+    }
+
+    #[test]
+    fn test_was_fired() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &3, &1);
+        assert!(client.was_fired(&p1, &3, &1));
+        assert!(!client.was_fired(&p1, &1, &3));
+        assert!(!client.was_fired(&p2, &3, &1));
+    }
+
+    #[test]
+    #[should_panic(expected = "x out of range")]
+    fn test_was_fired_out_of_bounds() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.was_fired(&p1, &5, &0);
+    }
+
+    fn start_with_timeout(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.initialize(&Config { turn_timeout: 300, start_grace_period: 60, ..test_config(env) });
+        start_game(env, client, p1, p2);
+    }
+
+    #[test]
+    fn test_grace_period_on_first_turn() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start_with_timeout(&env, &client, &p1, &p2);
+
+        // Past the plain 300s timeout, still inside the grace window
+        env.ledger().with_mut(|l| l.timestamp += 330);
+        assert!(client.try_claim_timeout(&p2).is_err());
+
+        env.ledger().with_mut(|l| l.timestamp += 31);
+        client.claim_timeout(&p2);
+        assert_eq!(client.get_state().winner, p2);
+        assert_eq!(client.get_forfeit_count(&p1), 1);
+        assert_eq!(client.get_forfeit_count(&p2), 0);
+    }
+
+    #[test]
+    fn test_later_turns_have_no_grace() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start_with_timeout(&env, &client, &p1, &p2);
+
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_state().turn_deadline, env.ledger().timestamp() + 300);
+
+        env.ledger().with_mut(|l| l.timestamp += 301);
+        client.claim_timeout(&p1);
+        assert_eq!(client.get_state().winner, p1);
+    }
+
+    #[test]
+    #[should_panic(expected = "NOT_INITIALIZED")]
+    fn test_fire_shot_before_initialize() {
+        let (_env, p1, _p2, client) = setup_uninitialized();
+        client.fire_shot(&p1, &0, &0);
+    }
+
+    #[test]
+    fn test_views_work_before_initialize() {
+        let (_env, p1, _p2, client) = setup_uninitialized();
+        assert!(client.try_join_game(&p1).is_err());
+        assert!(client.get_config().is_none());
+        assert_eq!(client.get_board_size(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "WAITING_FOR_PLAYERS")]
+    fn test_commit_before_opponent_joins() {
+        let (env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        client.commit_board(&p1, &hash(&env, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "COMMIT_PHASE_OVER")]
+    fn test_commit_after_playing_starts() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        client.commit_board(&p1, &hash(&env, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "NOT_A_PLAYER")]
+    fn test_fire_shot_by_outsider() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        client.fire_shot(&Address::generate(&env), &0, &0);
+    }
+
+    #[test]
+    fn test_remaining_turn_time() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        start_with_timeout(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_remaining_turn_time(), 300);
+
+        env.ledger().with_mut(|l| l.timestamp += 120);
+        assert_eq!(client.get_remaining_turn_time(), 180);
+
+        env.ledger().with_mut(|l| l.timestamp += 500);
+        assert_eq!(client.get_remaining_turn_time(), 0);
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        let before = client.get_state_hash();
+        assert_eq!(client.get_state_hash(), before);
+        let expected: BytesN<32> = env.crypto().sha256(&client.get_state().to_xdr(&env)).into();
+        assert_eq!(before, expected);
+
+        client.fire_shot(&p1, &0, &0);
+        let after = client.get_state_hash();
+        assert_ne!(after, before);
+        assert_eq!(client.get_state_hash(), after);
+    }
+
+    #[test]
+    fn test_get_deadline() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        assert_eq!(client.get_deadline(), None);
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        start_with_timeout(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_100);
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_deadline(), Some(1_400));
+
+        client.surrender(&p1);
+        assert_eq!(client.get_deadline(), None);
+    }
+
+    #[test]
+    fn test_get_commit_deadline() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { commit_timeout: 600, ..test_config(&env) });
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        client.join_game(&p1);
+        assert_eq!(client.get_commit_deadline(), None);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_050);
+        client.join_game(&p2);
+        assert_eq!(client.get_commit_deadline(), Some(1_650));
+        client.commit_board(&p1, &hash(&env, 1));
+        assert_eq!(client.get_commit_deadline(), Some(1_650));
+
+        client.commit_board(&p2, &hash(&env, 2));
+        assert_eq!(client.get_commit_deadline(), None);
+    }
+
+    #[test]
+    fn test_no_commit_deadline_without_timeout() {
+        let (_env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert_eq!(client.get_state().phase, GamePhase::Commit);
+        assert_eq!(client.get_commit_deadline(), None);
+    }
+
+    #[test]
+    fn test_no_deadline_without_timeout() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        assert_eq!(client.get_deadline(), None);
+    }
+}
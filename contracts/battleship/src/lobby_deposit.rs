@@ -0,0 +1,187 @@
+// Anti-spam deposit for opening a lobby. With Config.lobby_deposit set, the
+// player who opens a lobby escrows that much of the stake asset. It comes
+// back when a second player fills the lobby or the lobby is reset, and goes
+// to the admin when nobody joins within Config.lobby_ttl and the lobby is
+// expired with expire_lobby. The deposit and what happened to it stay
+// readable by session id. initialize refuses a deposit with no stake asset
+// to hold it in.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::events::publish;
+use crate::{
+    assert_initialized, assert_not_reentrant, clear_game, load_config, load_game, load_timeline,
+    stake_token, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey,
+    GamePhase,
+};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepositStatus {
+    Held,
+    Refunded,
+    Forfeited,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LobbyDeposit {
+    pub amount: i128,
+    pub depositor: Address,
+    pub status: DepositStatus,
+}
+
+fn load_deposit(env: &Env, session_id: u32) -> Option<LobbyDeposit> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, LobbyDeposit>(&DataKey::LobbyDeposit(session_id))
+}
+
+fn settle(env: &Env, session_id: u32, mut deposit: LobbyDeposit, status: DepositStatus) {
+    deposit.status = status;
+    env.storage().persistent().set(&DataKey::LobbyDeposit(session_id), &deposit);
+}
+
+// Called as `depositor` opens the lobby for `session_id`; no-op without a deposit
+pub(crate) fn take_deposit(env: &Env, depositor: &Address, session_id: u32) {
+    let amount = load_config(env).map(|config| config.lobby_deposit).unwrap_or(0);
+    if amount <= 0 {
+        return;
+    }
+    stake_token(env).transfer(depositor, &env.current_contract_address(), &amount);
+    let deposit = LobbyDeposit { amount, depositor: depositor.clone(), status: DepositStatus::Held };
+    env.storage().persistent().set(&DataKey::LobbyDeposit(session_id), &deposit);
+}
+
+// Hands a held deposit back; safe to call for games without one
+pub(crate) fn refund_deposit(env: &Env, session_id: u32) {
+    let Some(deposit) = load_deposit(env, session_id) else {
+        return;
+    };
+    if deposit.status != DepositStatus::Held {
+        return;
+    }
+    let contract = env.current_contract_address();
+    stake_token(env).transfer(&contract, &deposit.depositor, &deposit.amount);
+    settle(env, session_id, deposit, DepositStatus::Refunded);
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// Close a lobby nobody joined within Config.lobby_ttl seconds of its
+    /// creation. Anyone may call it; the creator's deposit goes to the admin.
+    pub fn expire_lobby(env: Env, caller: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        caller.require_auth();
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::WaitingForPlayers, "Lobby already filled");
+        let ttl = load_config(&env).map(|config| config.lobby_ttl).unwrap_or(0);
+        assert!(ttl > 0, "Lobbies don't expire");
+        let expires_at = load_timeline(&env).created_at.saturating_add(ttl);
+        assert!(env.ledger().timestamp() >= expires_at, "Lobby not expired");
+
+        if let Some(deposit) = load_deposit(&env, state.session_id) {
+            if deposit.status == DepositStatus::Held {
+                let admin = load_config(&env).expect("Not initialized").admin;
+                let contract = env.current_contract_address();
+                stake_token(&env).transfer(&contract, &admin, &deposit.amount);
+                settle(&env, state.session_id, deposit, DepositStatus::Forfeited);
+            }
+        }
+        publish(&env, &state, "lobby_expired", state.player1.clone());
+        clear_game(&env);
+    }
+
+    pub fn get_lobby_deposit(env: Env, session_id: u32) -> Option<LobbyDeposit> {
+        load_deposit(&env, session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DepositStatus, LobbyDeposit};
+    use crate::tests::{setup_staking_with_config, setup_uninitialized, test_config};
+    use crate::{BattleshipContractClient, Config};
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::{token, Address, Env};
+
+    // 10-unit deposit, lobbies expire after an hour
+    fn setup_deposit(env: &Env, client: &BattleshipContractClient, players: &[&Address]) -> Address {
+        let config = Config { lobby_deposit: 10, lobby_ttl: 3_600, ..test_config(env) };
+        setup_staking_with_config(env, client, players, config)
+    }
+
+    #[test]
+    fn test_deposit_refunded_when_lobby_fills() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_deposit(&env, &client, &[&p1, &p2]));
+
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+        assert_eq!(token.balance(&p1), 990);
+        assert_eq!(
+            client.get_lobby_deposit(&session_id),
+            Some(LobbyDeposit { amount: 10, depositor: p1.clone(), status: DepositStatus::Held })
+        );
+
+        // Only the lobby's creator pays
+        client.join_game(&p2);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(token.balance(&p2), 1000);
+        assert_eq!(client.get_lobby_deposit(&session_id).unwrap().status, DepositStatus::Refunded);
+    }
+
+    #[test]
+    fn test_deposit_refunded_on_reset() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_deposit(&env, &client, &[&p1, &p2]));
+
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+        client.reset_game(&p1);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(client.get_lobby_deposit(&session_id).unwrap().status, DepositStatus::Refunded);
+    }
+
+    #[test]
+    fn test_deposit_forfeited_on_expiry() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_deposit(&env, &client, &[&p1, &p2]));
+
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+
+        env.ledger().with_mut(|l| l.timestamp = 4_599);
+        assert!(client.try_expire_lobby(&p2).is_err());
+
+        env.ledger().with_mut(|l| l.timestamp = 4_600);
+        client.expire_lobby(&p2);
+        assert!(client.try_get_state().is_err());
+        assert_eq!(token.balance(&p1), 990);
+        assert_eq!(token.balance(&client.address), 0);
+        assert_eq!(token.balance(&client.get_config().unwrap().admin), 10);
+        assert_eq!(client.get_lobby_deposit(&session_id).unwrap().status, DepositStatus::Forfeited);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lobby deposit needs a stake asset")]
+    fn test_deposit_needs_stake_asset() {
+        let (env, _p1, _p2, client) = setup_uninitialized();
+        client.initialize(&Config { lobby_deposit: 10, ..test_config(&env) });
+    }
+
+    #[test]
+    #[should_panic(expected = "Lobby already filled")]
+    fn test_filled_lobby_cannot_expire() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        setup_deposit(&env, &client, &[&p1, &p2]);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        env.ledger().with_mut(|l| l.timestamp += 3_600);
+        client.expire_lobby(&p1);
+    }
+}
@@ -0,0 +1,392 @@
+// Rating-banded matchmaking. Queued players escrow their stake up front
+// (their auth is only available at enqueue time), so pairing can open a
+// fully-funded game without either player signing again. A newcomer pairs
+// with the closest-rated waiting player at the same stake, as long as the
+// gap fits that player's band — RATING_BAND, widened by RATING_BAND_WIDEN for
+// every minute they've waited. Ties go to whoever has waited longest.
+//
+// A newcomer who arrives while the game slot is busy waits in line, and so
+// can whoever they would have matched. Once the slot is free, match_queue
+// pairs the closest-rated pair among the `max_entries` longest-waiting
+// players, judged by the band of the one who has waited longer. There is
+// one game slot, so a call opens at most one game.
+//
+// The queue holds at most MAX_QUEUE_LEN players and each address may enqueue
+// once per ledger, so pairing — which compares every entry with every other —
+// and leave_queue stay cheap however many addresses try to flood it. Players
+// blocked since they queued are passed over, though they can still leave.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::reveal::assert_reveals_checkable;
+use crate::{
+    assert_initialized, assert_not_reentrant, has_game, is_blocked, load_stats, new_game,
+    rate_limit, rate_limit_join, save_game, stake_token, stamp, track_joined, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, DataKey, GameMode, GameOptions, GamePhase,
+    GameState,
+};
+
+const RATING_BAND: u32 = 100;
+const RATING_BAND_WIDEN: u32 = 50;
+const MAX_QUEUE_LEN: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueueEntry {
+    pub enqueued_at: u64,
+    pub player: Address,
+    pub rating: u32,
+    pub stake: i128,
+}
+
+fn band(env: &Env, entry: &QueueEntry) -> u32 {
+    let minutes_waited = (env.ledger().timestamp() - entry.enqueued_at) / 60;
+    let widen = (minutes_waited as u32).saturating_mul(RATING_BAND_WIDEN);
+    RATING_BAND.saturating_add(widen)
+}
+
+// Index of the best opponent for a newcomer rated `rating`, if any fits
+fn find_opponent(env: &Env, queue: &Vec<QueueEntry>, rating: u32, stake: i128) -> Option<u32> {
+    let mut best: Option<(u32, u32)> = None;
+    for (index, entry) in queue.iter().enumerate() {
+        let gap = entry.rating.abs_diff(rating);
+        if entry.stake != stake || gap > band(env, &entry) || is_blocked(env, &entry.player) {
+            continue;
+        }
+        if best.map(|(_, best_gap)| gap < best_gap).unwrap_or(true) {
+            best = Some((index as u32, gap));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+// Indexes (earlier, later) of the closest-rated pair among the first
+// `max_entries` of the queue at the same stake whose gap fits the earlier
+// entry's band, if any
+fn find_pair(env: &Env, queue: &Vec<QueueEntry>, max_entries: u32) -> Option<(u32, u32)> {
+    let scanned = queue.len().min(max_entries);
+    let mut blocked = Vec::new(env);
+    for entry in queue.iter().take(scanned as usize) {
+        blocked.push_back(is_blocked(env, &entry.player));
+    }
+    let mut best: Option<(u32, u32, u32)> = None;
+    for (first, waiting) in queue.iter().enumerate().take(scanned as usize) {
+        if blocked.get_unchecked(first as u32) {
+            continue;
+        }
+        let band = band(env, &waiting);
+        for (second, entry) in queue.iter().enumerate().take(scanned as usize).skip(first + 1) {
+            let gap = entry.rating.abs_diff(waiting.rating);
+            if entry.stake != waiting.stake || gap > band || blocked.get_unchecked(second as u32) {
+                continue;
+            }
+            if best.map(|(_, _, best_gap)| gap < best_gap).unwrap_or(true) {
+                best = Some((first as u32, second as u32, gap));
+            }
+        }
+    }
+    best.map(|(first, second, _)| (first, second))
+}
+
+// Opens a fully funded game between two players whose stakes are already
+// escrowed; `player1` is the one who waited
+fn open_match(env: &Env, player1: &Address, player2: Address, stake: i128) {
+    rate_limit_join(env, player1);
+    rate_limit_join(env, &player2);
+
+    let options = GameOptions { stake, ..Default::default() };
+    let mut state: GameState = new_game(env, player1, &options);
+    state.player2 = player2;
+    state.p1_joined = true;
+    state.p2_joined = true;
+    state.pot = stake * 2;
+    state.phase = GamePhase::Commit;
+    stamp(env, |t| &mut t.commit_started_at);
+    track_joined(env, &state.player1, state.session_id);
+    track_joined(env, &state.player2, state.session_id);
+    save_game(env, &state);
+}
+
+fn load_queue(env: &Env) -> Vec<QueueEntry> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<QueueEntry>>(&DataKey::MatchQueue)
+        .unwrap_or(Vec::new(env))
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // Pairs `player` with the best-rated match at the same stake if the game
+    // slot is free; otherwise they wait in line. Returns true if a game was
+    // created.
+    pub fn enqueue(env: Env, player: Address, stake: i128) -> bool {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+        assert!(stake >= 0, "Invalid stake");
+        assert_reveals_checkable(&env, stake, GameMode::Classic);
+        assert!(!is_blocked(&env, &player), "ADDRESS_BLOCKED");
+        rate_limit(&env, DataKey::LastEnqueue(player.clone()));
+
+        let mut queue = load_queue(&env);
+        assert!(!queue.iter().any(|e| e.player == player), "Already queued");
+
+        if stake > 0 {
+            stake_token(&env).transfer(&player, &env.current_contract_address(), &stake);
+        }
+
+        let rating = load_stats(&env, &player).rating;
+        let slot_free = !has_game(&env);
+        let opponent = find_opponent(&env, &queue, rating, stake);
+
+        let (Some(index), true) = (opponent, slot_free) else {
+            assert!(queue.len() < MAX_QUEUE_LEN, "Queue full");
+            let enqueued_at = env.ledger().timestamp();
+            queue.push_back(QueueEntry { enqueued_at, player, rating, stake });
+            env.storage().persistent().set(&DataKey::MatchQueue, &queue);
+            return false;
+        };
+
+        let opponent = queue.get_unchecked(index);
+        queue.remove(index);
+        env.storage().persistent().set(&DataKey::MatchQueue, &queue);
+        open_match(&env, &opponent.player, player, stake);
+        true
+    }
+
+    // Pairs the best-matched of the `max_entries` longest-waiting players,
+    // once the game slot is free. Anyone may call it. Returns true if a game
+    // was created.
+    pub fn match_queue(env: Env, max_entries: u32) -> bool {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        if has_game(&env) {
+            return false;
+        }
+
+        let mut queue = load_queue(&env);
+        let Some((first, second)) = find_pair(&env, &queue, max_entries) else {
+            return false;
+        };
+        let (waiting, later) = (queue.get_unchecked(first), queue.get_unchecked(second));
+        queue.remove(second);
+        queue.remove(first);
+        env.storage().persistent().set(&DataKey::MatchQueue, &queue);
+        open_match(&env, &waiting.player, later.player, waiting.stake);
+        true
+    }
+
+    // Leaves the queue and refunds the escrowed stake
+    pub fn leave_queue(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let mut queue = load_queue(&env);
+        let index = queue.iter()
+            .position(|e| e.player == player)
+            .expect("Not queued") as u32;
+        let entry = queue.get_unchecked(index);
+        queue.remove(index);
+        env.storage().persistent().set(&DataKey::MatchQueue, &queue);
+
+        if entry.stake > 0 {
+            stake_token(&env).transfer(&env.current_contract_address(), &player, &entry.stake);
+        }
+    }
+
+    pub fn get_queue_length(env: Env) -> u32 {
+        load_queue(&env).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{setup, setup_staking, setup_uninitialized};
+    use crate::{load_stats, DataKey, PlayerStats};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, token};
+
+    fn set_rating(env: &Env, client: &BattleshipContractClient, player: &Address, rating: u32) {
+        env.as_contract(&client.address, || {
+            let stats = PlayerStats { rating, ..load_stats(env, player) };
+            env.storage().persistent().set(&DataKey::PlayerStats(player.clone()), &stats);
+        });
+    }
+
+    #[test]
+    fn test_pairs_closest_rating_first() {
+        let (env, p1, p2, client) = setup();
+        let far = p1;
+        let close = p2;
+        let newcomer = Address::generate(&env);
+        set_rating(&env, &client, &far, 1130);
+        set_rating(&env, &client, &close, 1250);
+
+        // Both are in the newcomer's band; `far` has waited longer but is
+        // 70 away, `close` only 50
+        assert!(!client.enqueue(&far, &0));
+        assert!(!client.enqueue(&close, &0));
+        assert!(client.enqueue(&newcomer, &0));
+        assert_eq!(client.get_state().player1, close);
+        assert_eq!(client.get_queue_length(), 1);
+    }
+
+    #[test]
+    fn test_band_widens_with_wait() {
+        let (env, p1, p2, client) = setup();
+        set_rating(&env, &client, &p1, 1500);
+
+        // 300 apart: outside the starting band
+        assert!(!client.enqueue(&p1, &0));
+        assert!(!client.enqueue(&p2, &0));
+        client.leave_queue(&p2);
+
+        // Four minutes later p1's band is 100 + 4 × 50
+        env.ledger().with_mut(|l| {
+            l.timestamp += 240;
+            l.sequence_number += 1;
+        });
+        assert!(client.enqueue(&p2, &0));
+        assert_eq!(client.get_state().player1, p1);
+    }
+
+    #[test]
+    fn test_matching_stakes_create_game() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = setup_staking(&env, &client, &[&p1, &p2]);
+
+        assert!(!client.enqueue(&p1, &100));
+        assert_eq!(client.get_queue_length(), 1);
+
+        assert!(client.enqueue(&p2, &100));
+        assert_eq!(client.get_queue_length(), 0);
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Commit);
+        assert_eq!((state.player1, state.player2), (p1, p2));
+        assert_eq!(state.pot, 200);
+        assert_eq!(token::Client::new(&env, &token).balance(&client.address), 200);
+    }
+
+    #[test]
+    fn test_mismatched_stakes_wait() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = setup_staking(&env, &client, &[&p1, &p2]);
+
+        assert!(!client.enqueue(&p1, &100));
+        assert!(!client.enqueue(&p2, &50));
+        assert_eq!(client.get_queue_length(), 2);
+        assert!(client.get_config().is_some());
+
+        client.leave_queue(&p2);
+        assert_eq!(client.get_queue_length(), 1);
+        assert_eq!(token::Client::new(&env, &token).balance(&p2), 1000);
+    }
+
+    #[test]
+    fn test_pairing_is_rate_limited() {
+        let (env, p1, p2, client) = setup();
+        assert!(!client.enqueue(&p2, &0));
+
+        // A join this ledger rules out a matched game until the next one
+        client.join_game(&p1);
+        client.reset_game(&p1);
+        assert!(client.try_enqueue(&p1, &0).is_err());
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        assert!(client.enqueue(&p1, &0));
+        assert_eq!(client.get_state().player2, p1);
+    }
+
+    #[test]
+    fn test_match_queue_pairs_waiting_players() {
+        let (env, p1, p2, client) = setup();
+        let (busy, far) = (Address::generate(&env), Address::generate(&env));
+        set_rating(&env, &client, &far, 1400);
+        client.join_game(&busy);
+
+        // Slot busy: everyone waits, though p1 and p2 would match
+        assert!(!client.enqueue(&far, &0));
+        assert!(!client.enqueue(&p1, &0));
+        assert!(!client.enqueue(&p2, &0));
+        assert!(!client.match_queue(&MAX_QUEUE_LEN));
+
+        client.reset_game(&busy);
+        assert!(client.match_queue(&MAX_QUEUE_LEN));
+        let state = client.get_state();
+        assert_eq!((state.player1, state.player2), (p1, p2));
+        assert_eq!(state.phase, GamePhase::Commit);
+        assert_eq!(client.get_queue_length(), 1);
+        assert!(!client.match_queue(&MAX_QUEUE_LEN));
+    }
+
+    #[test]
+    fn test_match_queue_scans_max_entries() {
+        let (env, p1, p2, client) = setup();
+        let (busy, far) = (Address::generate(&env), Address::generate(&env));
+        set_rating(&env, &client, &far, 1400);
+        client.join_game(&busy);
+        assert!(!client.enqueue(&far, &0));
+        assert!(!client.enqueue(&p1, &0));
+        assert!(!client.enqueue(&p2, &0));
+        client.reset_game(&busy);
+
+        // p2 is third in line
+        assert!(!client.match_queue(&2));
+        assert!(client.match_queue(&3));
+        assert_eq!(client.get_state().player2, p2);
+    }
+
+    #[test]
+    fn test_free_games_need_no_token() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+
+        assert!(!client.enqueue(&p1, &0));
+        assert!(client.enqueue(&p2, &0));
+
+        // Slot is busy now, so the next player waits even with nobody to match
+        assert!(!client.enqueue(&p3, &0));
+        assert_eq!(client.get_queue_length(), 1);
+    }
+
+    #[test]
+    fn test_queue_is_capped() {
+        let (env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        for _ in 0..MAX_QUEUE_LEN {
+            assert!(!client.enqueue(&Address::generate(&env), &0));
+        }
+        assert!(client.try_enqueue(&Address::generate(&env), &0).is_err());
+        assert_eq!(client.get_queue_length(), MAX_QUEUE_LEN);
+    }
+
+    #[test]
+    fn test_enqueue_is_rate_limited() {
+        let (env, p1, _p2, client) = setup();
+        assert!(!client.enqueue(&p1, &0));
+        client.leave_queue(&p1);
+        assert!(client.try_enqueue(&p1, &0).is_err());
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        assert!(!client.enqueue(&p1, &0));
+    }
+
+    #[test]
+    fn test_blocked_players_not_paired() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        assert!(!client.enqueue(&p1, &0));
+        client.add_to_blocklist(&p1);
+
+        assert!(!client.enqueue(&p2, &0));
+        assert!(!client.match_queue(&MAX_QUEUE_LEN));
+        assert!(client.try_get_state().is_err());
+
+        // p1 can still get out of the queue, and p2 pairs with someone else
+        client.leave_queue(&p1);
+        assert!(client.enqueue(&p3, &0));
+        assert_eq!(client.get_state().player1, p2);
+    }
+}
@@ -0,0 +1,95 @@
+// Per-phase sets of game ids for operational dashboards — how many games sit
+// in Commit, how many are Paused, and so on. save_game moves a game between
+// sets whenever its phase changes; a game deleted on reset leaves its set.
+// Finished games stay in Finished, matching their Game(id) entry.
+
+use soroban_sdk::{contractimpl, Env, Vec};
+
+use crate::{
+    BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey, GamePhase,
+    GAME_TTL_EXTEND_TO, GAME_TTL_THRESHOLD,
+};
+
+fn load_index(env: &Env, phase: &GamePhase) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<u32>>(&DataKey::GamesInPhase(phase.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+// The sets live as long as the games in them
+fn save_index(env: &Env, phase: GamePhase, ids: &Vec<u32>) {
+    let key = DataKey::GamesInPhase(phase);
+    env.storage().persistent().set(&key, ids);
+    env.storage().persistent().extend_ttl(&key, GAME_TTL_THRESHOLD, GAME_TTL_EXTEND_TO);
+}
+
+// Moves `session_id` from the `from` set to the `to` set; None is "no game"
+pub(crate) fn reindex(env: &Env, session_id: u32, from: Option<GamePhase>, to: Option<GamePhase>) {
+    if from == to {
+        return;
+    }
+    if let Some(phase) = from {
+        let mut ids = load_index(env, &phase);
+        if let Some(i) = ids.first_index_of(session_id) {
+            ids.remove(i);
+        }
+        save_index(env, phase, &ids);
+    }
+    if let Some(phase) = to {
+        let mut ids = load_index(env, &phase);
+        ids.push_back(session_id);
+        save_index(env, phase, &ids);
+    }
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// Up to `limit` ids of games currently in `phase`, oldest transition first.
+    pub fn get_games_by_phase(env: Env, phase: GamePhase, limit: u32) -> Vec<u32> {
+        let ids = load_index(&env, &phase);
+        ids.slice(0..limit.min(ids.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, setup};
+    use crate::GamePhase;
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_games_by_phase() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        let first = client.get_state().session_id;
+        assert_eq!(
+            client.get_games_by_phase(&GamePhase::WaitingForPlayers, &10),
+            vec![&env, first]
+        );
+
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+        client.surrender(&p1);
+        client.reset_game(&p1);
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let second = client.get_state().session_id;
+
+        assert_eq!(client.get_games_by_phase(&GamePhase::Finished, &10), vec![&env, first]);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Commit, &10), vec![&env, second]);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Playing, &10).len(), 0);
+        assert_eq!(client.get_games_by_phase(&GamePhase::WaitingForPlayers, &10).len(), 0);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Commit, &0).len(), 0);
+
+        // An abandoned game is deleted, so it drops out of its phase too
+        client.reset_game(&p1);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Commit, &10).len(), 0);
+        assert_eq!(client.get_games_by_phase(&GamePhase::Finished, &10), vec![&env, first]);
+    }
+}
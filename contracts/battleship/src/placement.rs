@@ -0,0 +1,143 @@
+// Client-side placement check, on-chain so every client agrees on the rules.
+// `board` is the row-major cell list the board hasher takes (1 = ship) and
+// `ships` is the fleet as (x, y, length) from each ship's top-left cell. A
+// ship runs either right or down from (x, y); the fleet is valid when some
+// choice of directions has every ship fit on the grid, no two ships share a
+// cell and together they cover exactly the board's ship cells, with
+// TOTAL_SHIPS ships — the default fleet size. That's 2^TOTAL_SHIPS layouts
+// to try at most.
+
+use soroban_sdk::{contractimpl, Env, Vec};
+
+use crate::{
+    cell_bit, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, BOARD_SIZE,
+    TOTAL_SHIPS,
+};
+
+fn board_mask(board: &Vec<u32>) -> Option<u32> {
+    if board.len() != BOARD_SIZE * BOARD_SIZE {
+        return None;
+    }
+    let mut mask = 0;
+    for (i, cell) in board.iter().enumerate() {
+        match cell {
+            0 => {}
+            1 => mask |= 1 << i,
+            _ => return None,
+        }
+    }
+    Some(mask)
+}
+
+// The cells of one ship, or None if it leaves the grid
+fn ship_mask(x: u32, y: u32, length: u32, across: bool) -> Option<u32> {
+    if length == 0 || x >= BOARD_SIZE || y >= BOARD_SIZE {
+        return None;
+    }
+    let (dx, dy) = if across { (1, 0) } else { (0, 1) };
+    let mut mask = 0;
+    for i in 0..length {
+        let (cx, cy) = (x + dx * i, y + dy * i);
+        if cx >= BOARD_SIZE || cy >= BOARD_SIZE {
+            return None;
+        }
+        mask |= cell_bit(cx, cy);
+    }
+    Some(mask)
+}
+
+fn valid_placement(board: &Vec<u32>, ships: &Vec<(u32, u32, u32)>) -> bool {
+    let Some(board) = board_mask(board) else {
+        return false;
+    };
+    if ships.len() != TOTAL_SHIPS {
+        return false;
+    }
+    // Bit i of `directions` set: ship i runs across
+    (0..1u32 << TOTAL_SHIPS).any(|directions| covers(board, ships, directions))
+}
+
+fn covers(board: u32, ships: &Vec<(u32, u32, u32)>, directions: u32) -> bool {
+    let mut covered = 0;
+    for (i, (x, y, length)) in ships.iter().enumerate() {
+        let Some(ship) = ship_mask(x, y, length, directions & (1 << i) != 0) else {
+            return false;
+        };
+        if covered & ship != 0 {
+            return false;
+        }
+        covered |= ship;
+    }
+    covered == board
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn validate_placement(_env: Env, board: Vec<u32>, ships: Vec<(u32, u32, u32)>) -> bool {
+        valid_placement(&board, &ships)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup;
+    use soroban_sdk::{Env, Vec};
+
+    fn cells(env: &Env, ship_cells: &[(u32, u32)]) -> Vec<u32> {
+        let mut board = Vec::new(env);
+        for i in 0..25u32 {
+            board.push_back(ship_cells.contains(&(i % 5, i / 5)) as u32);
+        }
+        board
+    }
+
+    #[test]
+    fn test_valid_placement() {
+        let (env, _p1, _p2, client) = setup();
+        let board = cells(&env, &[(0, 0), (1, 0), (4, 1), (4, 2), (4, 3), (2, 4)]);
+        let ships = Vec::from_array(&env, [(0, 0, 2), (4, 1, 3), (2, 4, 1)]);
+        assert!(client.validate_placement(&board, &ships));
+    }
+
+    #[test]
+    fn test_vertical_ship_beside_another() {
+        let (env, _p1, _p2, client) = setup();
+        // (0,0)-(0,1) runs down even though (1,0) holds a ship
+        let board = cells(&env, &[(0, 0), (0, 1), (1, 0), (3, 3)]);
+        let ships = Vec::from_array(&env, [(0, 0, 2), (1, 0, 1), (3, 3, 1)]);
+        assert!(client.validate_placement(&board, &ships));
+    }
+
+    #[test]
+    fn test_overlapping_ships() {
+        let (env, _p1, _p2, client) = setup();
+        let board = cells(&env, &[(0, 0), (1, 0), (2, 0), (3, 3)]);
+        let ships = Vec::from_array(&env, [(0, 0, 3), (1, 0, 2), (3, 3, 1)]);
+        assert!(!client.validate_placement(&board, &ships));
+    }
+
+    #[test]
+    fn test_ship_off_the_grid() {
+        let (env, _p1, _p2, client) = setup();
+        let board = cells(&env, &[(3, 0), (4, 0), (0, 2), (0, 4)]);
+        let ships = Vec::from_array(&env, [(3, 0, 3), (0, 2, 1), (0, 4, 1)]);
+        assert!(!client.validate_placement(&board, &ships));
+
+        let ships = Vec::from_array(&env, [(3, 0, 2), (0, 2, 1), (5, 4, 1)]);
+        assert!(!client.validate_placement(&board, &ships));
+    }
+
+    #[test]
+    fn test_wrong_ship_count() {
+        let (env, _p1, _p2, client) = setup();
+        let board = cells(&env, &[(0, 0), (2, 2)]);
+        let ships = Vec::from_array(&env, [(0, 0, 1), (2, 2, 1)]);
+        assert!(!client.validate_placement(&board, &ships));
+
+        // The ships also have to account for every ship cell on the board
+        let board = cells(&env, &[(0, 0), (2, 2), (4, 4), (1, 3)]);
+        let ships = Vec::from_array(&env, [(0, 0, 1), (2, 2, 1), (4, 4, 1)]);
+        assert!(!client.validate_placement(&board, &ships));
+    }
+}
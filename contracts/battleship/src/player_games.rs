@@ -0,0 +1,246 @@
+// Per-player game index: session ids of the games a player has joined, split
+// into active and finished. Updated on join, when a game ends, and when an
+// unfinished game is reset away. The finished list's length is also kept on
+// its own so it can be read without loading the list.
+// Opponents are kept in a set of their own, DataKey::Opponents(player),
+// added to as each game is decided, so co-op teammates and drawn games never
+// show up and reading it never touches the games themselves.
+
+use soroban_sdk::{contractimpl, Address, Env, Vec};
+
+use crate::recent_games::load_result;
+use crate::{
+    bump, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey, GameResult,
+    GameState,
+};
+
+fn load(env: &Env, key: &DataKey) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<u32>>(key)
+        .unwrap_or(Vec::new(env))
+}
+
+fn load_opponents(env: &Env, player: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<Address>>(&DataKey::Opponents(player.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+// Called as a game is decided between `winner` and `loser`
+pub(crate) fn track_opponents(env: &Env, winner: &Address, loser: &Address) {
+    for (player, opponent) in [(winner, loser), (loser, winner)] {
+        let mut opponents = load_opponents(env, player);
+        if !opponents.contains(opponent) {
+            opponents.push_back(opponent.clone());
+            env.storage().persistent().set(&DataKey::Opponents(player.clone()), &opponents);
+        }
+    }
+}
+
+fn remove_active(env: &Env, player: &Address, session_id: u32) {
+    let key = DataKey::ActiveGames(player.clone());
+    let mut active = load(env, &key);
+    if let Some(index) = active.first_index_of(session_id) {
+        active.remove(index);
+        env.storage().persistent().set(&key, &active);
+    }
+}
+
+pub(crate) fn track_joined(env: &Env, player: &Address, session_id: u32) {
+    let key = DataKey::ActiveGames(player.clone());
+    let mut active = load(env, &key);
+    active.push_back(session_id);
+    env.storage().persistent().set(&key, &active);
+}
+
+pub(crate) fn track_finished(env: &Env, state: &GameState) {
+    for player in [&state.player1, &state.player2] {
+        remove_active(env, player, state.session_id);
+        let key = DataKey::FinishedGames(player.clone());
+        let mut finished = load(env, &key);
+        finished.push_back(state.session_id);
+        env.storage().persistent().set(&key, &finished);
+
+        let count_key = DataKey::FinishedCount(player.clone());
+        let mut count = env.storage().persistent().get::<DataKey, u32>(&count_key).unwrap_or(0);
+        bump(&mut count);
+        env.storage().persistent().set(&count_key, &count);
+    }
+}
+
+// `new` took over `old`'s seat in the game
+pub(crate) fn track_replaced(env: &Env, old: &Address, new: &Address, session_id: u32) {
+    remove_active(env, old, session_id);
+    track_joined(env, new, session_id);
+}
+
+// The game's session id changed from `old` to state.session_id
+pub(crate) fn track_renamed(env: &Env, state: &GameState, old: u32) {
+    for player in [&state.player1, &state.player2] {
+        remove_active(env, player, old);
+        track_joined(env, player, state.session_id);
+    }
+}
+
+// The game was reset before it ended — it's neither active nor finished
+pub(crate) fn track_abandoned(env: &Env, state: &GameState) {
+    if state.p1_joined {
+        remove_active(env, &state.player1, state.session_id);
+    }
+    if state.p2_joined {
+        remove_active(env, &state.player2, state.session_id);
+    }
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn get_active_games(env: Env, player: Address) -> Vec<u32> {
+        load(&env, &DataKey::ActiveGames(player))
+    }
+
+    pub fn get_finished_games(env: Env, player: Address) -> Vec<u32> {
+        load(&env, &DataKey::FinishedGames(player))
+    }
+
+    // Length of get_finished_games, from a single counter
+    pub fn get_player_games_count(env: Env, player: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<DataKey, u32>(&DataKey::FinishedCount(player))
+            .unwrap_or(0)
+    }
+
+    // Result of the player's latest finished game; None if they have none,
+    // or if it ended without a winner
+    pub fn get_last_game_result(env: Env, player: Address) -> Option<GameResult> {
+        let last = load(&env, &DataKey::FinishedGames(player)).last()?;
+        load_result(&env, last)
+    }
+
+    // Up to `limit` of the players `player` has won or lost a game against,
+    // each once, in the order first met, skipping the first `start`
+    pub fn get_opponents(env: Env, player: Address, start: u32, limit: u32) -> Vec<Address> {
+        let opponents = load_opponents(&env, &player);
+        let mut page = Vec::new(&env);
+        for opponent in opponents.iter().skip(start as usize).take(limit as usize) {
+            page.push_back(opponent);
+        }
+        page
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, setup};
+    use crate::{GameMode, GameOptions, GamePhase};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Vec};
+
+    #[test]
+    fn test_active_and_finished_games() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let first = client.get_state().session_id;
+        client.surrender(&p2);
+        client.reset_game(&p1);
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        let second = client.get_state().session_id;
+
+        assert_eq!(client.get_active_games(&p1), Vec::from_array(&env, [second]));
+        assert_eq!(client.get_finished_games(&p1), Vec::from_array(&env, [first]));
+        assert_eq!(client.get_finished_games(&p2), Vec::from_array(&env, [first]));
+
+        // Resetting an unfinished game drops it from the index entirely
+        client.reset_game(&p1);
+        assert!(client.get_active_games(&p2).is_empty());
+        assert_eq!(client.get_finished_games(&p2).len(), 1);
+    }
+
+    #[test]
+    fn test_player_games_count() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        assert_eq!(client.get_player_games_count(&p1), 0);
+
+        for opponent in [&p2, &p3] {
+            client.join_game(&p1);
+            client.join_game(opponent);
+            client.surrender(opponent);
+            client.reset_game(&p1);
+            env.ledger().with_mut(|l| l.sequence_number += 1);
+        }
+
+        // An abandoned game isn't history
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.reset_game(&p1);
+
+        assert_eq!(client.get_player_games_count(&p1), 2);
+        assert_eq!(client.get_player_games_count(&p2), 1);
+        assert_eq!(client.get_player_games_count(&p3), 1);
+        assert_eq!(client.get_player_games_count(&p1), client.get_finished_games(&p1).len());
+    }
+
+    #[test]
+    fn test_opponents() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        assert!(client.get_opponents(&p1, &0, &10).is_empty());
+
+        for opponent in [&p2, &p3, &p2] {
+            client.join_game(&p1);
+            client.join_game(opponent);
+            client.surrender(opponent);
+            client.reset_game(&p1);
+            env.ledger().with_mut(|l| l.sequence_number += 1);
+        }
+
+        let both = Vec::from_array(&env, [p2.clone(), p3.clone()]);
+        assert_eq!(client.get_opponents(&p1, &0, &10), both);
+        assert_eq!(client.get_opponents(&p1, &1, &1), Vec::from_array(&env, [p3.clone()]));
+        assert_eq!(client.get_opponents(&p2, &0, &10), Vec::from_array(&env, [p1.clone()]));
+        assert_eq!(client.get_opponents(&p3, &0, &10), Vec::from_array(&env, [p1]));
+    }
+
+    #[test]
+    fn test_teammates_are_not_opponents() {
+        let (env, p1, p2, client) = setup();
+        let options = GameOptions { mode: GameMode::CoOp(1), ..Default::default() };
+        client.create_game(&p1, &options);
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 1));
+        client.surrender(&p1);
+
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+        assert!(client.get_opponents(&p1, &0, &10).is_empty());
+    }
+
+    #[test]
+    fn test_last_game_result() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.surrender(&p1);
+        client.reset_game(&p1);
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let session_id = client.get_state().session_id;
+        client.surrender(&p2);
+
+        let last = client.get_last_game_result(&p1).unwrap();
+        assert_eq!(last.session_id, session_id);
+        assert_eq!((&last.winner, &last.loser), (&p1, &p2));
+        assert_eq!(client.get_last_game_result(&p2), Some(last));
+        assert_eq!(client.get_last_game_result(&Address::generate(&env)), None);
+    }
+}
@@ -0,0 +1,185 @@
+// Global "recent matches" feed: the last RECENT_GAMES_CAP results across all
+// players, oldest first in storage, newest first out of get_recent_games.
+// Every result is also kept by session id, uncapped, for per-game lookups
+// such as get_result_by_session, which lets hub-side records be matched up
+// with the game that produced them.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::{
+    BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey, GameState,
+};
+
+const RECENT_GAMES_CAP: u32 = 20;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameResult {
+    pub finished_at: u64,
+    pub forfeit: bool,
+    pub loser: Address,
+    pub session_id: u32,
+    pub winner: Address,
+}
+
+/// A finished game's result with the final score, keyed by session id.
+/// `hits_on_loser` is how many of the loser's cells were hit, and
+/// `hits_on_winner` the reverse.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchSummary {
+    pub finished_at: u64,
+    pub forfeit: bool,
+    pub hits_on_loser: u32,
+    pub hits_on_winner: u32,
+    pub loser: Address,
+    pub moves: u32,
+    pub session_id: u32,
+    pub stake: i128,
+    pub winner: Address,
+}
+
+fn load_recent(env: &Env) -> Vec<GameResult> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<GameResult>>(&DataKey::RecentGames)
+        .unwrap_or(Vec::new(env))
+}
+
+pub(crate) fn load_result(env: &Env, session_id: u32) -> Option<GameResult> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, GameResult>(&DataKey::Result(session_id))
+}
+
+pub(crate) fn push_recent(env: &Env, result: GameResult) {
+    env.storage().persistent().set(&DataKey::Result(result.session_id), &result);
+    let mut recent = load_recent(env);
+    recent.push_back(result);
+    if recent.len() > RECENT_GAMES_CAP {
+        recent.pop_front();
+    }
+    env.storage().persistent().set(&DataKey::RecentGames, &recent);
+}
+
+// Rewrites a recorded result after a dispute: Some((winner, loser)) swaps
+// in the new outcome, None strikes the result for a draw
+pub(crate) fn amend_result(env: &Env, session_id: u32, outcome: Option<(Address, Address)>) {
+    let key = DataKey::Result(session_id);
+    let Some(mut result) = load_result(env, session_id) else {
+        return;
+    };
+    let mut recent = load_recent(env);
+    let index = recent.iter().position(|entry| entry.session_id == session_id);
+    match outcome {
+        Some((winner, loser)) => {
+            result.winner = winner;
+            result.loser = loser;
+            env.storage().persistent().set(&key, &result);
+            if let Some(index) = index {
+                recent.set(index as u32, result);
+            }
+        }
+        None => {
+            env.storage().persistent().remove(&key);
+            if let Some(index) = index {
+                recent.remove(index as u32);
+            }
+        }
+    }
+    env.storage().persistent().set(&DataKey::RecentGames, &recent);
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// Up to `limit` of the latest finished games, most recent first.
+    pub fn get_recent_games(env: Env, limit: u32) -> Vec<GameResult> {
+        let recent = load_recent(&env);
+        let mut latest = Vec::new(&env);
+        for result in recent.iter().rev().take(limit as usize) {
+            latest.push_back(result);
+        }
+        latest
+    }
+
+    pub fn get_result_by_session(env: Env, session_id: u32) -> Option<MatchSummary> {
+        let result = load_result(&env, session_id)?;
+        let state = env.storage()
+            .persistent()
+            .get::<DataKey, GameState>(&DataKey::Game(session_id))?;
+        let (hits_on_winner, hits_on_loser) = if result.winner == state.player1 {
+            (state.hits_on_p1, state.hits_on_p2)
+        } else {
+            (state.hits_on_p2, state.hits_on_p1)
+        };
+        Some(MatchSummary {
+            finished_at: result.finished_at,
+            forfeit: result.forfeit,
+            hits_on_loser,
+            hits_on_winner,
+            loser: result.loser,
+            moves: state.move_number,
+            session_id,
+            stake: state.stake,
+            winner: result.winner,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RECENT_GAMES_CAP;
+    use crate::tests::{hash, proof, setup};
+    use crate::GameOptions;
+    use soroban_sdk::{testutils::Ledger, Vec};
+
+    #[test]
+    fn test_recent_games_newest_first() {
+        let (env, p1, p2, client) = setup();
+        let mut sessions = Vec::new(&env);
+        for _ in 0..RECENT_GAMES_CAP + 2 {
+            env.ledger().with_mut(|l| l.sequence_number += 1);
+            client.join_game(&p1);
+            client.join_game(&p2);
+            sessions.push_back(client.get_state().session_id);
+            client.surrender(&p2);
+            client.reset_game(&p1);
+        }
+
+        let recent = client.get_recent_games(&3);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent.get_unchecked(0).session_id, sessions.get_unchecked(RECENT_GAMES_CAP + 1));
+        assert_eq!(recent.get_unchecked(2).session_id, sessions.get_unchecked(RECENT_GAMES_CAP - 1));
+        assert!(recent.get_unchecked(0).forfeit);
+        assert_eq!(recent.get_unchecked(0).winner, p1);
+
+        // The buffer is capped — the oldest games fell off
+        assert_eq!(client.get_recent_games(&100).len(), RECENT_GAMES_CAP);
+    }
+
+    #[test]
+    fn test_result_by_session() {
+        let (env, p1, p2, client) = setup();
+        env.ledger().with_mut(|l| l.timestamp = 500);
+        client.create_game(&p1, &GameOptions { ships_to_sink_p2: 1, ..Default::default() });
+        client.join_game(&p2);
+        let session_id = client.get_state().session_id;
+        assert_eq!(client.get_result_by_session(&session_id), None);
+
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+        client.fire_shot(&p1, &4, &4);
+        client.submit_response(&p2, &4, &4, &proof(&env, false));
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        client.reset_game(&p1);
+
+        let summary = client.get_result_by_session(&session_id).unwrap();
+        assert_eq!((summary.winner, summary.loser), (p1, p2));
+        assert_eq!((summary.hits_on_winner, summary.hits_on_loser), (0, 1));
+        assert_eq!((summary.moves, summary.finished_at), (2, 500));
+        assert!(!summary.forfeit);
+        assert_eq!(client.get_result_by_session(&(session_id + 1)), None);
+    }
+}
@@ -0,0 +1,194 @@
+// Relayed joins. A player signs the message from get_join_message with the
+// ed25519 key behind their account, and anyone (a relayer paying the fees)
+// submits it with join_game_signed. The message names the lobby's session id
+// and the last ledger it may be submitted in, and each signed join consumes
+// the player's join nonce, so a signature can't be replayed, reused on
+// another game or held back for later.
+//
+// Only an open lobby can be joined this way, and only an unwagered one: a
+// stake is pulled from the player with a token transfer that needs their own
+// auth, which a signature over this message doesn't give.
+//
+// Message format: "battleship:join" ‖ XDR(contract address) ‖ session id
+// (u32 BE) ‖ expiry ledger (u32 BE) ‖ nonce (u64 BE).
+
+use soroban_sdk::{
+    contractimpl, xdr::{FromXdr, ToXdr}, Address, Bytes, BytesN, Env,
+};
+
+use crate::{
+    assert_initialized, assert_not_reentrant, join, load_game, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, DataKey, GameOptions, GamePhase,
+};
+
+const JOIN_DOMAIN: &[u8] = b"battleship:join";
+
+// XDR prefix of an account address: ScVal::Address, ScAddress::Account,
+// PublicKey::Ed25519 — followed by the 32-byte key
+const ACCOUNT_XDR_PREFIX: [u8; 12] = [0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0];
+
+fn join_nonce(env: &Env, player: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u64>(&DataKey::JoinNonce(player.clone()))
+        .unwrap_or(0)
+}
+
+fn join_message(env: &Env, session_id: u32, expires_at: u32, nonce: u64) -> Bytes {
+    let mut message = Bytes::from_slice(env, JOIN_DOMAIN);
+    message.append(&env.current_contract_address().to_xdr(env));
+    message.append(&Bytes::from_array(env, &session_id.to_be_bytes()));
+    message.append(&Bytes::from_array(env, &expires_at.to_be_bytes()));
+    message.append(&Bytes::from_array(env, &nonce.to_be_bytes()));
+    message
+}
+
+// The account address controlled by `public_key`
+fn account_address(env: &Env, public_key: &BytesN<32>) -> Address {
+    let mut xdr = Bytes::from_array(env, &ACCOUNT_XDR_PREFIX);
+    xdr.append(&public_key.clone().into());
+    Address::from_xdr(env, &xdr).expect("Invalid public key")
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// The bytes `player` must sign for their next join_game_signed into
+    /// lobby `session_id`, valid up to and including ledger `expires_at`.
+    pub fn get_join_message(env: Env, player: Address, session_id: u32, expires_at: u32) -> Bytes {
+        join_message(&env, session_id, expires_at, join_nonce(&env, &player))
+    }
+
+    /// join_game on behalf of `player`, authorized by their signature instead
+    /// of require_auth. Joins the open, unwagered lobby `session_id`.
+    pub fn join_game_signed(
+        env: Env,
+        player: Address,
+        session_id: u32,
+        expires_at: u32,
+        public_key: BytesN<32>,
+        signature: BytesN<64>,
+    ) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        assert!(account_address(&env, &public_key) == player, "Key doesn't match player");
+        assert!(env.ledger().sequence() <= expires_at, "Signature expired");
+
+        let state = load_game(&env).expect("No lobby");
+        assert!(state.phase == GamePhase::WaitingForPlayers, "Game already started");
+        assert!(state.session_id == session_id, "Wrong game");
+        assert!(state.stake == 0, "Relayed joins can't stake");
+
+        let nonce = join_nonce(&env, &player);
+        let message = join_message(&env, session_id, expires_at, nonce);
+        env.crypto().ed25519_verify(&public_key, &message, &signature);
+        env.storage().persistent().set(&DataKey::JoinNonce(player.clone()), &(nonce + 1));
+
+        join(&env, player, GameOptions::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::account_address;
+    use crate::tests::{setup, setup_staking, setup_uninitialized};
+    use crate::GameOptions;
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::{testutils::Ledger, Bytes, BytesN, Env};
+
+    fn sign(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+        let mut buf = [0u8; 128];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buf[..len]);
+        BytesN::from_array(env, &key.sign(&buf[..len]).to_bytes())
+    }
+
+    #[test]
+    fn test_relayed_join() {
+        let (env, p1, _p2, client) = setup();
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        let player = account_address(&env, &public_key);
+
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+        let message = client.get_join_message(&player, &session_id, &100);
+        let signature = sign(&env, &key, &message);
+        client.join_game_signed(&player, &session_id, &100, &public_key, &signature);
+        assert_eq!(client.get_state().player2, player);
+
+        // The nonce moved on, so the same signature is dead
+        client.reset_game(&p1);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &100, &public_key, &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_relayed_join_bound_to_game_and_ledger() {
+        let (env, p1, _p2, client) = setup();
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        let player = account_address(&env, &public_key);
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+
+        // Signed for another game
+        let other = sign(&env, &key, &client.get_join_message(&player, &(session_id + 1), &100));
+        assert!(client
+            .try_join_game_signed(&player, &(session_id + 1), &100, &public_key, &other)
+            .is_err());
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &100, &public_key, &other)
+            .is_err());
+
+        // Past its expiry ledger
+        let expires_at = env.ledger().sequence();
+        let message = client.get_join_message(&player, &session_id, &expires_at);
+        let signature = sign(&env, &key, &message);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &expires_at, &public_key, &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_relayed_join_refuses_stakes() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        setup_staking(&env, &client, &[&p1]);
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        let player = account_address(&env, &public_key);
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        let session_id = client.get_state().session_id;
+        let signature = sign(&env, &key, &client.get_join_message(&player, &session_id, &100));
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &100, &public_key, &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_relayed_join_wrong_signer() {
+        let (env, p1, _p2, client) = setup();
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let impostor = SigningKey::from_bytes(&[4u8; 32]);
+        let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+        let player = account_address(&env, &public_key);
+
+        client.join_game(&p1);
+        let session_id = client.get_state().session_id;
+        let signature = sign(&env, &impostor, &client.get_join_message(&player, &session_id, &100));
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &100, &public_key, &signature)
+            .is_err());
+
+        let impostor_key = BytesN::from_array(&env, &impostor.verifying_key().to_bytes());
+        assert!(client
+            .try_join_game_signed(&player, &session_id, &100, &impostor_key, &signature)
+            .is_err());
+    }
+}
@@ -0,0 +1,232 @@
+// Rematch handshake. Once a game is Finished (and any pot claimed), either
+// player may propose a rematch, escrowing their stake right away; the new
+// game only starts when the other player accepts and pays theirs. Declining,
+// withdrawing or resetting the game refunds the proposer. Accepting clears
+// the old game as reset_game would and seats both players as join would, so
+// it waits out an open dispute and refuses blocked or rate-limited players.
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::dispute::{assert_payout_allowed, dispute_open};
+use crate::{
+    assert_initialized, assert_not_reentrant, clear_game, is_blocked, load_game, new_game,
+    other_player, rate_limit_join, save_game, stake_token, stamp, start_practice, track_joined,
+    BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey, GameMode,
+    GameOptions, GamePhase, GameState,
+};
+
+fn proposer(env: &Env) -> Option<Address> {
+    env.storage().instance().get::<DataKey, Address>(&DataKey::RematchProposal)
+}
+
+// Drops a pending proposal, returning the proposer's escrowed stake
+pub(crate) fn cancel_rematch(env: &Env, state: &GameState) {
+    if let Some(proposer) = proposer(env) {
+        env.storage().instance().remove(&DataKey::RematchProposal);
+        if state.stake > 0 {
+            stake_token(env).transfer(&env.current_contract_address(), &proposer, &state.stake);
+        }
+    }
+}
+
+fn finished_game(env: &Env, player: &Address) -> GameState {
+    let state = load_game(env).expect("No game");
+    assert!(state.phase == GamePhase::Finished, "Game not finished");
+    assert!(*player == state.player1 || *player == state.player2, "Not a player");
+    state
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // Escrows the proposer's stake until the opponent answers
+    pub fn propose_rematch(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let state = finished_game(&env, &player);
+        assert!(state.pot == 0, "Winnings unclaimed");
+        assert!(proposer(&env).is_none(), "Rematch already proposed");
+
+        env.storage().instance().set(&DataKey::RematchProposal, &player);
+        if state.stake > 0 {
+            stake_token(&env).transfer(&player, &env.current_contract_address(), &state.stake);
+        }
+    }
+
+    // Starts the rematch in the Commit phase, same seats and settings
+    pub fn accept_rematch(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let old = finished_game(&env, &player);
+        let proposer = proposer(&env).expect("No rematch proposed");
+        assert!(proposer != player, "Can't accept your own proposal");
+        // The old result must be final before the slot moves on
+        assert!(!dispute_open(&env, &old), "Dispute open");
+        assert!(old.pot == 0, "Winnings unclaimed");
+        assert_payout_allowed(&env, &old);
+        for seat in [&old.player1, &old.player2] {
+            assert!(!is_blocked(&env, seat), "ADDRESS_BLOCKED");
+            rate_limit_join(&env, seat);
+        }
+        // The proposer's stake stays escrowed for the new game
+        env.storage().instance().remove(&DataKey::RematchProposal);
+        clear_game(&env);
+
+        if old.stake > 0 {
+            stake_token(&env).transfer(&player, &env.current_contract_address(), &old.stake);
+        }
+
+        let options = GameOptions {
+            mode: old.mode,
+            ship_cells: old.ship_cells,
+            ships_to_sink_p1: old.ships_to_sink_p1,
+            ships_to_sink_p2: old.ships_to_sink_p2,
+            stake: old.stake,
+            time_budget: old.time_budget,
+        };
+        let mut state = new_game(&env, &old.player1, &options);
+        state.player2 = old.player2.clone();
+        state.p1_joined = true;
+        state.p2_joined = true;
+        state.pot = old.stake * 2;
+        state.phase = GamePhase::Commit;
+        stamp(&env, |t| &mut t.commit_started_at);
+        if state.mode == GameMode::Practice {
+            start_practice(&env, &mut state);
+        }
+        track_joined(&env, &state.player1, state.session_id);
+        track_joined(&env, &state.player2, state.session_id);
+        save_game(&env, &state);
+    }
+
+    // The opponent declines, or the proposer withdraws
+    pub fn decline_rematch(env: Env, player: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let state = finished_game(&env, &player);
+        let proposer = proposer(&env).expect("No rematch proposed");
+        assert!(player == proposer || player == other_player(&state, &proposer), "Not a player");
+        cancel_rematch(&env, &state);
+    }
+
+    pub fn get_rematch_proposer(env: Env) -> Option<Address> {
+        proposer(&env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        board_hash, proof, salt, setup, setup_staking, setup_uninitialized, start_game, test_config,
+    };
+    use crate::{BattleshipContractClient, Config, GameMode, GameOptions, GamePhase};
+    use soroban_sdk::{testutils::Ledger, token, Address, Env};
+
+    // A wagered one-ship game won by p1, pot already paid out
+    fn finish_and_claim(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.create_game(p1, &GameOptions { stake: 100, ships_to_sink_p2: 1, ..Default::default() });
+        client.join_game(p2);
+        client.commit_board(p1, &board_hash(client, 0b111));
+        client.commit_board(p2, &board_hash(client, 0b1));
+        client.fire_shot(p1, &0, &0);
+        client.submit_response(p2, &0, &0, &proof(env, true));
+        client.reveal_board(p1, &0b111, &salt(env));
+        client.reveal_board(p2, &0b1, &salt(env));
+        client.claim_winnings(p1);
+    }
+
+    #[test]
+    fn test_rematch_handshake() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_and_claim(&env, &client, &p1, &p2);
+        let first = client.get_state().session_id;
+
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.propose_rematch(&p2);
+        assert_eq!(client.get_rematch_proposer(), Some(p2.clone()));
+        assert_eq!(token.balance(&p2), 800);
+
+        // Nothing starts until the opponent agrees
+        assert_eq!(client.get_state().session_id, first);
+        assert!(client.try_accept_rematch(&p2).is_err());
+
+        client.accept_rematch(&p1);
+        let state = client.get_state();
+        assert_ne!(state.session_id, first);
+        assert_eq!(state.phase, GamePhase::Commit);
+        assert_eq!((state.player1, state.player2), (p1.clone(), p2.clone()));
+        assert_eq!(state.pot, 200);
+        assert_eq!(token.balance(&p1), 1000);
+        assert_eq!(client.get_rematch_proposer(), None);
+    }
+
+    #[test]
+    fn test_declined_rematch_refunds() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_and_claim(&env, &client, &p1, &p2);
+
+        client.propose_rematch(&p1);
+        assert_eq!(token.balance(&p1), 1000);
+        client.decline_rematch(&p2);
+
+        assert_eq!(token.balance(&p1), 1100);
+        assert_eq!(client.get_rematch_proposer(), None);
+        assert!(client.try_accept_rematch(&p2).is_err());
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+    }
+
+    #[test]
+    fn test_rematch_waits_for_dispute() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { dispute_window: 600, ..test_config(&env) });
+        start_game(&env, &client, &p1, &p2);
+        client.surrender(&p2);
+        let first = client.get_state().session_id;
+
+        client.raise_dispute(&p2);
+        client.propose_rematch(&p1);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        assert!(client.try_accept_rematch(&p2).is_err());
+        assert_eq!(client.get_state().session_id, first);
+
+        let admin = client.get_config().unwrap().admin;
+        client.resolve_dispute(&admin, &Some(p1.clone()));
+        client.accept_rematch(&p2);
+        assert_ne!(client.get_state().session_id, first);
+    }
+
+    #[test]
+    fn test_practice_rematch_skips_commit() {
+        let (env, p1, p2, client) = setup();
+        client.create_game(&p1, &GameOptions { mode: GameMode::Practice, ..Default::default() });
+        client.join_game(&p2);
+        client.surrender(&p2);
+
+        client.propose_rematch(&p2);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.accept_rematch(&p1);
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+    }
+
+    #[test]
+    fn test_blocked_player_cannot_rematch() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.surrender(&p2);
+
+        client.propose_rematch(&p2);
+        client.add_to_blocklist(&p2);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        assert!(client.try_accept_rematch(&p1).is_err());
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+    }
+}
@@ -0,0 +1,719 @@
+// Post-game board reveals and the pull payment they gate. After Finished,
+// each player reveals their board as a bitmask of ship cells (bit
+// y * BOARD_SIZE + x) plus its salt. A reveal is only accepted if it agrees
+// with every answer that player gave during the game and, when a board
+// hasher is configured, hashes to the committed board_hash. Transparent
+// games skip the hasher and must match the plain board instead. Wagered
+// games need one or the other, or a reveal would prove nothing. The winner
+// can claim a wagered pot once both boards are in, less Config.rake_bps of
+// it, which goes to the admin.
+//
+// Once both boards are in, boards that are identical or a mirror image of
+// each other raise a "suspicious_boards" event for moderators. Two honest
+// players rarely land on the same layout, colluding ones often do; it's only
+// a flag, nothing is penalised.
+//
+// A game that ends with a loser opens a window of Config.reveal_window
+// seconds (DEFAULT_REVEAL_WINDOW if unset) for the reveals, so a loser can't
+// quietly sit on a board that never matched its commitment. Missing it costs
+// the loser MISSED_REVEAL_PENALTY rating points, applied once: by anyone
+// calling penalize_missed_reveal after the deadline, or on the spot when the
+// reveal comes in late. It also stops holding up the pot: past the deadline
+// a missing reveal forfeits the game, so a winner who has revealed may claim
+// without the loser's board, and a winner who hasn't loses the game — and
+// the pot — to a loser who has. reset_game settles whatever is still
+// escrowed the same way, or refunds both stakes when neither board is in.
+// A forfeit before both boards are committed never reaches any of this:
+// forfeit_game refunds the stakes, and the win stands without reveals.
+//
+// soroban-sdk has no BN254 Poseidon2 host function, so the hash is delegated
+// to Config.hasher — a contract running the same sponge as circuits/hash_only.
+
+use soroban_sdk::{contractclient, contractimpl, contracttype, Address, BytesN, Env, Vec};
+
+use crate::dispute::assert_payout_allowed;
+use crate::events::publish;
+use crate::fleet::fleet_size;
+use crate::transparent::plain_board;
+use crate::{
+    assert_initialized, assert_not_reentrant, cell_bit, load_config, load_game, load_stats,
+    other_player, reassign_win, save_game, stake_token, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, DataKey, GameMode, GamePhase, GameState, BOARD_SIZE, NO_SHOT,
+};
+
+const MISSED_REVEAL_PENALTY: u32 = 32;
+const DEFAULT_REVEAL_WINDOW: u64 = 86_400; // 1 day
+
+// (winner's share, admin's rake) of `pot`
+fn split_pot(env: &Env, pot: i128) -> (i128, i128) {
+    let rake_bps = load_config(env).map(|config| config.rake_bps).unwrap_or(0);
+    let rake = pot * rake_bps as i128 / 10_000;
+    (pot - rake, rake)
+}
+
+#[allow(dead_code)]
+#[contractclient(name = "BoardHasherClient")]
+pub trait BoardHasher {
+    // Poseidon2(cells ++ [salt]) with cells row-major, 1 = ship
+    fn hash_board(env: Env, cells: Vec<u32>, salt: BytesN<32>) -> BytesN<32>;
+}
+
+// Called as every wagered game is opened
+pub(crate) fn assert_reveals_checkable(env: &Env, stake: i128, mode: GameMode) {
+    if stake == 0 || mode == GameMode::Transparent {
+        return;
+    }
+    let hasher = load_config(env).and_then(|config| config.hasher);
+    assert!(hasher.is_some(), "Wagers need a board hasher");
+}
+
+fn hash_board(env: &Env, hasher: &Address, board: u32, salt: &BytesN<32>) -> BytesN<32> {
+    let mut cells = Vec::new(env);
+    for i in 0..BOARD_SIZE * BOARD_SIZE {
+        cells.push_back((board >> i) & 1);
+    }
+    BoardHasherClient::new(env, hasher).hash_board(&cells, salt)
+}
+
+/// `deadline` is when the loser's reveal window closes (0 = no window) and
+/// `penalized` whether the loser has been docked for missing it.
+#[contracttype]
+#[derive(Clone, Debug, Default)]
+pub struct BoardReveals {
+    pub board_p1: u32,
+    pub board_p2: u32,
+    pub deadline: u64,
+    pub penalized: bool,
+    pub revealed_p1: bool,
+    pub revealed_p2: bool,
+}
+
+fn load_reveals(env: &Env) -> BoardReveals {
+    env.storage()
+        .instance()
+        .get::<DataKey, BoardReveals>(&DataKey::Reveals)
+        .unwrap_or_default()
+}
+
+// Called as a game ends with a winner
+pub(crate) fn open_reveal_window(env: &Env) {
+    let window = match load_config(env).map(|config| config.reveal_window) {
+        Some(window) if window > 0 => window,
+        _ => DEFAULT_REVEAL_WINDOW,
+    };
+    let mut reveals = load_reveals(env);
+    reveals.deadline = env.ledger().timestamp().saturating_add(window);
+    env.storage().instance().set(&DataKey::Reveals, &reveals);
+}
+
+fn deadline_passed(env: &Env, reveals: &BoardReveals) -> bool {
+    reveals.deadline > 0 && env.ledger().timestamp() > reveals.deadline
+}
+
+fn window_missed(env: &Env, reveals: &BoardReveals) -> bool {
+    deadline_passed(env, reveals) && !reveals.penalized
+}
+
+fn revealed(state: &GameState, reveals: &BoardReveals, player: &Address) -> bool {
+    if *player == state.player1 {
+        reveals.revealed_p1
+    } else {
+        reveals.revealed_p2
+    }
+}
+
+// Whether the winner may be paid yet: once both boards are in, or once the
+// deadline has passed with the winner's board in
+fn winner_payable(env: &Env, state: &GameState, reveals: &BoardReveals) -> bool {
+    let loser = other_player(state, &state.winner);
+    revealed(state, reveals, &state.winner)
+        && (revealed(state, reveals, &loser) || deadline_passed(env, reveals))
+}
+
+// Pays the pot, less the rake, to `to`
+fn pay_out(env: &Env, state: &mut GameState, to: &Address) {
+    let (payout, rake) = split_pot(env, state.pot);
+    state.pot = 0;
+    save_game(env, state);
+
+    let token = stake_token(env);
+    let contract = env.current_contract_address();
+    token.transfer(&contract, to, &payout);
+    if rake > 0 {
+        let admin = load_config(env).expect("Not initialized").admin;
+        token.transfer(&contract, &admin, &rake);
+    }
+}
+
+// Past the deadline, a winner who never revealed forfeits the game to a
+// loser who did — unless the game ended before both boards were committed,
+// when the winner may have had nothing to reveal
+fn forfeit_unrevealed_winner(env: &Env, state: &mut GameState, reveals: &mut BoardReveals) {
+    if !state.has_winner || !deadline_passed(env, reveals) {
+        return;
+    }
+    if !(state.p1_committed && state.p2_committed) {
+        return;
+    }
+    let loser = other_player(state, &state.winner);
+    if revealed(state, reveals, &state.winner) || !revealed(state, reveals, &loser) {
+        return;
+    }
+    reassign_win(env, state);
+    save_game(env, state);
+    publish(env, state, "reveal_forfeit", loser);
+    if !reveals.penalized {
+        penalize(env, state, reveals);
+        env.storage().instance().set(&DataKey::Reveals, reveals);
+    }
+}
+
+// Called by reset_game on a finished game whose pot is still escrowed. Past
+// the reveal deadline whoever holds the win with their board in is paid;
+// otherwise neither board can be trusted and both stakes go back.
+pub(crate) fn settle_pot(env: &Env, state: &mut GameState) {
+    assert_payout_allowed(env, state);
+    let mut reveals = load_reveals(env);
+    forfeit_unrevealed_winner(env, state, &mut reveals);
+    if state.has_winner && winner_payable(env, state, &reveals) {
+        let winner = state.winner.clone();
+        pay_out(env, state, &winner);
+    } else {
+        assert!(deadline_passed(env, &reveals), "Winnings unclaimed");
+        state.pot = 0;
+        save_game(env, state);
+        let token = stake_token(env);
+        let contract = env.current_contract_address();
+        token.transfer(&contract, &state.player1, &state.stake);
+        token.transfer(&contract, &state.player2, &state.stake);
+    }
+    let loser = other_player(state, &state.winner);
+    if !revealed(state, &reveals, &loser) && window_missed(env, &reveals) {
+        penalize(env, state, &mut reveals);
+        env.storage().instance().set(&DataKey::Reveals, &reveals);
+    }
+    publish(env, state, "pot_settled", state.winner.clone());
+}
+
+fn penalize(env: &Env, state: &GameState, reveals: &mut BoardReveals) {
+    let loser = other_player(state, &state.winner);
+    let mut stats = load_stats(env, &loser);
+    stats.rating = stats.rating.saturating_sub(MISSED_REVEAL_PENALTY);
+    env.storage().persistent().set(&DataKey::PlayerStats(loser.clone()), &stats);
+    reveals.penalized = true;
+    publish(env, state, "reveal_missed", loser);
+}
+
+// The revealed board must hold exactly the fleet, contain every cell the
+// player answered "hit" for and none of the cells they answered "miss" for.
+fn check_board(state: &GameState, player: &Address, board: u32) {
+    let (fleet, hit_mask, shots_taken) = if *player == state.player1 {
+        (state.ships_to_sink_p1, state.hit_mask_p1, state.fired_mask_p2)
+    } else {
+        (state.ships_to_sink_p2, state.hit_mask_p2, state.fired_mask_p1)
+    };
+
+    // A shot left unanswered by a forfeit was never claimed either way
+    let mut answered = shots_taken;
+    if state.pending_shot_x != NO_SHOT && state.pending_shooter != *player {
+        answered &= !cell_bit(state.pending_shot_x, state.pending_shot_y);
+    }
+
+    assert!(board.count_ones() == fleet * state.ship_cells, "Wrong fleet size");
+    assert!(board & hit_mask == hit_mask, "Board contradicts a hit");
+    assert!(board & (answered & !hit_mask) == 0, "Board contradicts a miss");
+}
+
+// Reflects a board left-to-right (`flip_x`) and/or top-to-bottom (`flip_y`)
+fn mirror(board: u32, flip_x: bool, flip_y: bool) -> u32 {
+    let mut mirrored = 0;
+    for y in 0..BOARD_SIZE {
+        for x in 0..BOARD_SIZE {
+            if board & cell_bit(x, y) != 0 {
+                let mx = if flip_x { BOARD_SIZE - 1 - x } else { x };
+                let my = if flip_y { BOARD_SIZE - 1 - y } else { y };
+                mirrored |= cell_bit(mx, my);
+            }
+        }
+    }
+    mirrored
+}
+
+fn suspicious(board_p1: u32, board_p2: u32) -> bool {
+    [(false, false), (true, false), (false, true), (true, true)]
+        .iter()
+        .any(|&(flip_x, flip_y)| mirror(board_p1, flip_x, flip_y) == board_p2)
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn compute_board_hash(env: Env, board: u32, salt: BytesN<32>) -> BytesN<32> {
+        let hasher = load_config(&env)
+            .and_then(|config| config.hasher)
+            .expect("No board hasher configured");
+        hash_board(&env, &hasher, board, &salt)
+    }
+
+    pub fn reveal_board(env: Env, player: Address, board: u32, salt: BytesN<32>) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let state = load_game(&env).expect("No game");
+
+        assert!(state.phase == GamePhase::Finished, "Game not finished");
+        // A fleet's shots are tracked per grid, which check_board can't see
+        assert!(fleet_size(&state).is_none(), "Can't reveal a fleet");
+        check_board(&state, &player, board);
+
+        // A plain board's commitment is a sha256, which the hasher can't reproduce
+        if let Some(plain) = plain_board(&env, &state, &player) {
+            assert!(board == plain, "Board mismatch");
+        } else if let Some(hasher) = load_config(&env).and_then(|config| config.hasher) {
+            let committed = if player == state.player1 {
+                &state.board_hash_p1
+            } else {
+                &state.board_hash_p2
+            };
+            // The hasher yields one field, so only single-field commitments can match
+            let hash = Vec::from_array(&env, [hash_board(&env, &hasher, board, &salt)]);
+            assert!(hash == *committed, "Board hash mismatch");
+        }
+
+        let mut reveals = load_reveals(&env);
+        if player == state.player1 {
+            assert!(!reveals.revealed_p1, "P1 already revealed");
+            reveals.board_p1 = board;
+            reveals.revealed_p1 = true;
+        } else if player == state.player2 {
+            assert!(!reveals.revealed_p2, "P2 already revealed");
+            reveals.board_p2 = board;
+            reveals.revealed_p2 = true;
+        } else {
+            panic!("Not a player");
+        }
+
+        let late_loser = state.has_winner && player != state.winner;
+        if late_loser && window_missed(&env, &reveals) {
+            penalize(&env, &state, &mut reveals);
+        }
+        env.storage().instance().set(&DataKey::Reveals, &reveals);
+
+        let both_in = reveals.revealed_p1 && reveals.revealed_p2;
+        if both_in && suspicious(reveals.board_p1, reveals.board_p2) {
+            publish(&env, &state, "suspicious_boards", (reveals.board_p1, reveals.board_p2));
+        }
+    }
+
+    pub fn get_reveals(env: Env) -> BoardReveals {
+        load_reveals(&env)
+    }
+
+    /// Dock the loser MISSED_REVEAL_PENALTY rating for not revealing their
+    /// board before the reveal deadline. Anyone may call it.
+    pub fn penalize_missed_reveal(env: Env, caller: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        caller.require_auth();
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::Finished && state.has_winner, "No loser to penalize");
+
+        let mut reveals = load_reveals(&env);
+        let loser_revealed = if state.winner == state.player1 {
+            reveals.revealed_p2
+        } else {
+            reveals.revealed_p1
+        };
+        assert!(!loser_revealed, "Loser revealed");
+        assert!(!reveals.penalized, "Already penalized");
+        assert!(window_missed(&env, &reveals), "Reveal window open");
+
+        penalize(&env, &state, &mut reveals);
+        env.storage().instance().set(&DataKey::Reveals, &reveals);
+    }
+
+    /// Pull payment for the winner of a wagered game: once both boards are
+    /// revealed, or past the reveal deadline with only the winner's. Past
+    /// the deadline a loser whose board is in can claim from a winner whose
+    /// isn't, taking the win with it.
+    pub fn claim_winnings(env: Env, winner: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        winner.require_auth();
+
+        let mut state = load_game(&env).expect("No game");
+        let mut reveals = load_reveals(&env);
+        forfeit_unrevealed_winner(&env, &mut state, &mut reveals);
+
+        assert!(state.phase == GamePhase::Finished && state.has_winner, "Game not finished");
+        assert!(state.winner == winner, "Not the winner");
+        assert!(state.pot > 0, "Nothing to claim");
+        assert_payout_allowed(&env, &state);
+        assert!(winner_payable(&env, &state, &reveals), "Boards not revealed");
+
+        // Claiming past the deadline without the loser's board
+        let loser = other_player(&state, &winner);
+        if !revealed(&state, &reveals, &loser) && window_missed(&env, &reveals) {
+            penalize(&env, &state, &mut reveals);
+            env.storage().instance().set(&DataKey::Reveals, &reveals);
+        }
+        pay_out(&env, &mut state, &winner);
+    }
+
+    // What claim_winnings would pay `player` if they won with the current pot
+    pub fn get_payout_preview(env: Env, player: Address) -> i128 {
+        let state = load_game(&env).expect("No game");
+        assert!(player == state.player1 || player == state.player2, "Not a player");
+        split_pot(&env, state.pot).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        board_hash, fund_players, proof, salt, setup, setup_staking, setup_staking_with_config,
+        setup_uninitialized, start_game, start_game_with, test_config, MockHasher,
+    };
+    use crate::{Config, GameOptions, StakeAsset, INITIAL_RATING, PRACTICE_BOARD_HASH};
+    use soroban_sdk::testutils::{Address as _, Events, Ledger};
+    use soroban_sdk::{token, IntoVal, Symbol};
+
+    fn init_with_hasher(env: &Env, client: &BattleshipContractClient) {
+        let hasher = env.register(MockHasher, ());
+        client.initialize(&Config { hasher: Some(hasher), ..test_config(env) });
+    }
+
+    fn board(cells: &[(u32, u32)]) -> u32 {
+        cells.iter().fold(0, |mask, (x, y)| mask | cell_bit(*x, *y))
+    }
+
+    // P1 sinks P2's ships at (0,0), (1,1), (2,2) after a miss at (4,4); P1's
+    // own fleet is (0,4), (1,4), (2,4)
+    fn finish_wagered(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.create_game(p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(p2);
+        client.commit_board(p1, &board_hash(client, board(&[(0, 4), (1, 4), (2, 4)])));
+        client.commit_board(p2, &board_hash(client, board(&[(0, 0), (1, 1), (2, 2)])));
+
+        client.fire_shot(p1, &4, &4);
+        client.submit_response(p2, &4, &4, &proof(env, false));
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(p1, &coord.0, &coord.1);
+            client.submit_response(p2, &coord.0, &coord.1, &proof(env, true));
+        }
+    }
+
+    // p2 surrenders to p1 with a one-hour reveal window
+    fn finish_with_window(env: &Env, client: &BattleshipContractClient, p1: &Address, p2: &Address) {
+        client.initialize(&Config { reveal_window: 3_600, ..test_config(env) });
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        start_game(env, client, p1, p2);
+        client.surrender(p2);
+    }
+
+    #[test]
+    fn test_reveal_within_window() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        finish_with_window(&env, &client, &p1, &p2);
+        assert_eq!(client.get_reveals().deadline, 4_600);
+
+        env.ledger().with_mut(|l| l.timestamp = 4_600);
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+
+        env.ledger().with_mut(|l| l.timestamp = 10_000);
+        assert!(client.try_penalize_missed_reveal(&p1).is_err());
+        assert!(!client.get_reveals().penalized);
+        assert_eq!(client.get_player_stats(&p2).unwrap().rating, INITIAL_RATING - 16);
+    }
+
+    #[test]
+    fn test_missed_reveal_window() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        finish_with_window(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 4_600);
+        assert!(client.try_penalize_missed_reveal(&p1).is_err());
+
+        env.ledger().with_mut(|l| l.timestamp = 4_601);
+        client.penalize_missed_reveal(&p1);
+        assert!(client.get_reveals().penalized);
+        let rating = INITIAL_RATING - 16 - MISSED_REVEAL_PENALTY;
+        assert_eq!(client.get_player_stats(&p2).unwrap().rating, rating);
+
+        // Once only, even if the board turns up afterwards
+        assert!(client.try_penalize_missed_reveal(&p1).is_err());
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        assert_eq!(client.get_player_stats(&p2).unwrap().rating, rating);
+    }
+
+    #[test]
+    fn test_late_reveal_is_penalized() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        finish_with_window(&env, &client, &p1, &p2);
+
+        env.ledger().with_mut(|l| l.timestamp = 5_000);
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        assert!(client.get_reveals().penalized);
+        assert_eq!(
+            client.get_player_stats(&p2).unwrap().rating,
+            INITIAL_RATING - 16 - MISSED_REVEAL_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_payout_preview_matches_payout() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let config = Config { rake_bps: 500, ..test_config(&env) };
+        let admin = config.admin.clone();
+        let token = setup_staking_with_config(&env, &client, &[&p1, &p2], config);
+        finish_wagered(&env, &client, &p1, &p2);
+
+        // 5% of the 200 pot
+        assert_eq!(client.get_payout_preview(&p1), 190);
+        assert_eq!(client.get_payout_preview(&p2), 190);
+
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        client.reveal_board(&p1, &board(&[(0, 4), (1, 4), (2, 4)]), &salt(&env));
+        client.claim_winnings(&p1);
+        let token = token::Client::new(&env, &token);
+        assert_eq!(token.balance(&p1), 900 + 190);
+        assert_eq!(token.balance(&admin), 10);
+        assert_eq!(token.balance(&client.address), 0);
+    }
+
+    #[test]
+    fn test_no_payout_preview_unwagered() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        assert_eq!(client.get_payout_preview(&p1), 0);
+        assert!(client.try_get_payout_preview(&Address::generate(&env)).is_err());
+    }
+
+    #[test]
+    fn test_payout_waits_for_both_reveals() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_wagered(&env, &client, &p1, &p2);
+
+        assert!(client.try_claim_winnings(&p1).is_err());
+
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        assert!(client.try_claim_winnings(&p1).is_err());
+
+        client.reveal_board(&p1, &board(&[(0, 4), (1, 4), (2, 4)]), &salt(&env));
+        assert!(client.get_reveals().revealed_p1);
+        client.claim_winnings(&p1);
+        assert_eq!(token.balance(&p1), 1100);
+        assert_eq!(client.get_pot(), 0);
+    }
+
+    #[test]
+    fn test_winner_claims_past_reveal_deadline() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_wagered(&env, &client, &p1, &p2);
+        let deadline = client.get_reveals().deadline;
+        assert_eq!(deadline, DEFAULT_REVEAL_WINDOW);
+
+        // The loser never reveals
+        client.reveal_board(&p1, &board(&[(0, 4), (1, 4), (2, 4)]), &salt(&env));
+        env.ledger().with_mut(|l| l.timestamp = deadline);
+        assert!(client.try_claim_winnings(&p1).is_err());
+
+        env.ledger().with_mut(|l| l.timestamp = deadline + 1);
+        client.claim_winnings(&p1);
+        assert_eq!(token.balance(&p1), 1100);
+        assert!(client.get_reveals().penalized);
+    }
+
+    #[test]
+    fn test_unrevealed_winner_forfeits() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_wagered(&env, &client, &p1, &p2);
+
+        // Only the loser reveals
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        assert!(client.try_claim_winnings(&p2).is_err());
+
+        env.ledger().with_mut(|l| l.timestamp = DEFAULT_REVEAL_WINDOW + 1);
+        assert!(client.try_claim_winnings(&p1).is_err());
+        client.claim_winnings(&p2);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (900, 1100));
+
+        let state = client.get_state();
+        assert_eq!(state.winner, p2);
+        assert_eq!(client.get_last_game_result(&p1).unwrap().winner, p2);
+        let stats = client.get_player_stats(&p1).unwrap();
+        assert_eq!((stats.games_won, stats.games_lost), (0, 1));
+        assert_eq!(stats.rating, INITIAL_RATING - 16 - MISSED_REVEAL_PENALTY);
+    }
+
+    #[test]
+    fn test_reset_settles_unclaimed_pot() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_wagered(&env, &client, &p1, &p2);
+        assert!(client.try_reset_game(&p1).is_err());
+
+        // Neither board came in, so neither player is paid the pot
+        env.ledger().with_mut(|l| l.timestamp = DEFAULT_REVEAL_WINDOW + 1);
+        client.reset_game(&p2);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1000, 1000));
+        assert_eq!(token.balance(&client.address), 0);
+
+        // And the slot is free again
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p1);
+    }
+
+    #[test]
+    fn test_reset_pays_revealed_winner() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        finish_wagered(&env, &client, &p1, &p2);
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        client.reveal_board(&p1, &board(&[(0, 4), (1, 4), (2, 4)]), &salt(&env));
+
+        // Both boards are in, so the reset needn't wait
+        client.reset_game(&p2);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1100, 900));
+    }
+
+    #[test]
+    fn test_surrender_before_commit_refunds() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        client.commit_board(&p1, &board_hash(&client, board(&[(0, 4), (1, 4), (2, 4)])));
+
+        // p2 has no board to reveal, so the pot can't wait on one
+        client.surrender(&p1);
+        assert_eq!(client.get_state().winner, p2);
+        assert_eq!(client.get_pot(), 0);
+        assert_eq!((token.balance(&p1), token.balance(&p2)), (1000, 1000));
+        client.reveal_board(&p1, &board(&[(0, 4), (1, 4), (2, 4)]), &salt(&env));
+        env.ledger().with_mut(|l| l.timestamp = DEFAULT_REVEAL_WINDOW + 1);
+        assert!(client.try_claim_winnings(&p1).is_err());
+        client.reset_game(&p1);
+        assert_eq!(client.get_last_game_result(&p1).unwrap().winner, p2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wagers need a board hasher")]
+    fn test_wager_needs_hasher() {
+        let (env, p1, _p2, client) = setup_uninitialized();
+        let token = fund_players(&env, &[&p1]);
+        client.initialize(&Config { stake_asset: StakeAsset::Token(token), ..test_config(&env) });
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+    }
+
+    #[test]
+    #[should_panic(expected = "Board contradicts a miss")]
+    fn test_reveal_rejects_lie_about_miss() {
+        let (env, p1, p2, client) = setup();
+        let options = GameOptions { ships_to_sink_p2: 4, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        client.fire_shot(&p1, &4, &4);
+        client.submit_response(&p2, &4, &4, &proof(&env, false));
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        client.surrender(&p2);
+
+        // (4,4) was answered as a miss
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2), (4, 4)]), &salt(&env));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrong fleet size")]
+    fn test_reveal_rejects_wrong_fleet() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        setup_staking(&env, &client, &[&p1, &p2]);
+        finish_wagered(&env, &client, &p1, &p2);
+
+        client.reveal_board(&p1, &board(&[(0, 4)]), &salt(&env));
+    }
+
+    fn suspicious_flagged(env: &Env) -> bool {
+        env.events().all().iter().any(|(_, topics, _)| {
+            let name: Symbol = topics.get_unchecked(0).into_val(env);
+            name == Symbol::new(env, "suspicious_boards")
+        })
+    }
+
+    // P1 sinks P2's diagonal fleet; P2 never fires, so any P1 board of the
+    // right size reveals cleanly
+    fn reveal_pair(board_p1: u32) -> bool {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+
+        client.reveal_board(&p2, &board(&[(0, 0), (1, 1), (2, 2)]), &salt(&env));
+        client.reveal_board(&p1, &board_p1, &salt(&env));
+        suspicious_flagged(&env)
+    }
+
+    #[test]
+    fn test_identical_boards_flagged() {
+        assert!(reveal_pair(board(&[(0, 0), (1, 1), (2, 2)])));
+    }
+
+    #[test]
+    fn test_mirrored_boards_flagged() {
+        // P2's diagonal reflected left-to-right
+        assert!(reveal_pair(board(&[(4, 0), (3, 1), (2, 2)])));
+    }
+
+    #[test]
+    fn test_distinct_boards_not_flagged() {
+        assert!(!reveal_pair(board(&[(0, 4), (1, 4), (2, 4)])));
+    }
+
+    #[test]
+    fn test_compute_board_hash_prover_vector() {
+        let (env, _p1, _p2, client) = setup_uninitialized();
+        init_with_hasher(&env, &client);
+
+        // circuits/battleship/Prover.toml: ships at (0,0), (2,1), (4,4), salt 0xdeadbeef
+        let mut salt = [0u8; 32];
+        salt[28..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let hash = client.compute_board_hash(
+            &board(&[(0, 0), (2, 1), (4, 4)]),
+            &BytesN::from_array(&env, &salt),
+        );
+        assert_eq!(hash, BytesN::from_array(&env, &PRACTICE_BOARD_HASH));
+    }
+
+    #[test]
+    fn test_reveal_checks_committed_hash() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        init_with_hasher(&env, &client);
+
+        let board_p1 = board(&[(0, 4), (1, 4), (2, 4)]);
+        let board_p2 = board(&[(0, 0), (1, 1), (2, 2)]);
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_board(&p1, &client.compute_board_hash(&board_p1, &salt(&env)));
+        client.commit_board(&p2, &client.compute_board_hash(&board_p2, &salt(&env)));
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+
+        let wrong_salt = BytesN::from_array(&env, &[9u8; 32]);
+        assert!(client.try_reveal_board(&p2, &board_p2, &wrong_salt).is_err());
+
+        client.reveal_board(&p2, &board_p2, &salt(&env));
+        client.reveal_board(&p1, &board_p1, &salt(&env));
+        assert!(client.get_reveals().revealed_p2);
+    }
+}
@@ -0,0 +1,238 @@
+// Proven sinks for WinRule::ProvenSinks and WinRule::Points. Under those
+// rules the defender's proof also attests whether a hit sank the ship it
+// landed on, and what that ship was worth. ProvenSinks is won by sinking
+// every ship of the opponent's fleet rather than by counting hits; Points by
+// the sunk ships' values reaching the threshold, so a carrier worth 2 counts
+// double. The counts live under DataKey::ShipsSunk(session_id) as (ships sunk
+// in player1's fleet, ships sunk in player2's), and the points under
+// DataKey::Points(session_id) as (scored by player1, scored by player2).
+
+use soroban_sdk::{contractimpl, Address, Env};
+
+use crate::events::publish;
+use crate::{
+    bump, load_game, BattleshipContract, BattleshipContractArgs, BattleshipContractClient,
+    DataKey, GameState,
+};
+
+fn load_pair(env: &Env, key: &DataKey) -> (u32, u32) {
+    env.storage().persistent().get::<DataKey, (u32, u32)>(key).unwrap_or((0, 0))
+}
+
+fn load_sunk(env: &Env, session_id: u32) -> (u32, u32) {
+    load_pair(env, &DataKey::ShipsSunk(session_id))
+}
+
+fn load_points(env: &Env, session_id: u32) -> (u32, u32) {
+    load_pair(env, &DataKey::Points(session_id))
+}
+
+pub(crate) fn points_scored(env: &Env, state: &GameState, attacker: &Address) -> u32 {
+    let (points_p1, points_p2) = load_points(env, state.session_id);
+    if *attacker == state.player1 {
+        points_p1
+    } else {
+        points_p2
+    }
+}
+
+pub(crate) fn ships_sunk(env: &Env, state: &GameState, defender: &Address) -> u32 {
+    let (sunk_p1, sunk_p2) = load_sunk(env, state.session_id);
+    if *defender == state.player1 {
+        sunk_p1
+    } else {
+        sunk_p2
+    }
+}
+
+// One more of `defender`'s ships went down, scoring `value` for the attacker
+pub(crate) fn record_sink(env: &Env, state: &GameState, defender: &Address, value: u32) {
+    let mut sunk = load_sunk(env, state.session_id);
+    let mut points = load_points(env, state.session_id);
+    let (count, score) = if *defender == state.player1 {
+        (&mut sunk.0, &mut points.1)
+    } else {
+        (&mut sunk.1, &mut points.0)
+    };
+    bump(count);
+    *score = score.checked_add(value).expect("COUNTER_OVERFLOW");
+    let count = *count;
+    env.storage().persistent().set(&DataKey::ShipsSunk(state.session_id), &sunk);
+    env.storage().persistent().set(&DataKey::Points(state.session_id), &points);
+    publish(env, state, "ship_sunk", (defender.clone(), count, value));
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // (ships sunk in player1's fleet, in player2's), as proven so far
+    pub fn get_ships_sunk(env: Env) -> (u32, u32) {
+        let state = load_game(&env).expect("No game");
+        load_sunk(&env, state.session_id)
+    }
+
+    // (points scored by player1, by player2) from sunk ships' values
+    pub fn get_points(env: Env) -> (u32, u32) {
+        let state = load_game(&env).expect("No game");
+        load_points(&env, state.session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{proof, setup_uninitialized, start_game, start_game_with, test_config};
+    use crate::{Config, GameOptions, GamePhase, WinRule};
+
+    // A defender fleet of two 2-cell ships along row 0, at x 0-1 and 2-3. A
+    // hit sinks a ship when the ship's other cell is already in the hit mask
+    // the public inputs carry.
+    mod sink_verifier {
+        use soroban_sdk::{contract, contractimpl, Bytes, Env};
+
+        fn read_u32(bytes: &Bytes, at: u32) -> u32 {
+            (at..at + 4).fold(0, |acc, i| (acc << 8) | bytes.get(i).unwrap() as u32)
+        }
+
+        #[contract]
+        pub struct SinkVerifier;
+
+        #[contractimpl]
+        impl SinkVerifier {
+            pub fn verify_sink_proof(
+                _env: Env,
+                public_inputs: Bytes,
+                _proof: Bytes,
+            ) -> Option<(bool, u32)> {
+                // board hash, x, y, hit mask — one field each
+                assert_eq!(public_inputs.len(), 128);
+                let x = read_u32(&public_inputs, 60);
+                let hit_mask = read_u32(&public_inputs, 124);
+                if x > 3 {
+                    return Some((false, 0));
+                }
+                let other_cell = x ^ 1;
+                Some((true, (hit_mask & (1 << other_cell) != 0) as u32))
+            }
+        }
+    }
+
+    // Row 0 of the defender's board: a 2-cell carrier worth 2 at x 0-1 and a
+    // 1-cell boat worth 1 at x 2
+    mod points_verifier {
+        use soroban_sdk::{contract, contractimpl, Bytes, Env};
+
+        #[contract]
+        pub struct PointsVerifier;
+
+        #[contractimpl]
+        impl PointsVerifier {
+            pub fn verify_sink_proof(
+                _env: Env,
+                public_inputs: Bytes,
+                _proof: Bytes,
+            ) -> Option<(bool, u32)> {
+                let x = public_inputs.get(63).unwrap() as u32;
+                let hit_mask = public_inputs.get(127).unwrap() as u32;
+                match x {
+                    0 | 1 => Some((true, if hit_mask & (1 << (x ^ 1)) != 0 { 2 } else { 0 })),
+                    2 => Some((true, 1)),
+                    _ => Some((false, 0)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_win_on_points() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(points_verifier::PointsVerifier, ());
+        client.initialize(&Config {
+            verifier: Some(verifier),
+            win_rule: WinRule::Points(3),
+            ..test_config(&env)
+        });
+        start_game(&env, &client, &p1, &p2);
+
+        // The carrier's second cell sinks it for 2; the boat makes 3
+        let expected_points = [0, 2, 3];
+        for (x, points) in expected_points.into_iter().enumerate() {
+            let x = x as u32;
+            assert_eq!(client.get_state().phase, GamePhase::Playing);
+            client.fire_shot(&p1, &x, &0);
+            client.submit_response(&p2, &x, &0, &proof(&env, true));
+            assert_eq!(client.get_points(), (points, 0));
+        }
+
+        // Two ships down is enough when they're worth enough
+        assert_eq!(client.get_ships_sunk(), (0, 2));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+    }
+
+    #[test]
+    fn test_zero_points_rule_rejected() {
+        let (env, _p1, _p2, client) = setup_uninitialized();
+        let config = Config { win_rule: WinRule::Points(0), ..test_config(&env) };
+        assert!(client.try_initialize(&config).is_err());
+    }
+
+    #[test]
+    fn test_win_on_last_proven_sink() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(sink_verifier::SinkVerifier, ());
+        client.initialize(&Config {
+            verifier: Some(verifier),
+            win_rule: WinRule::ProvenSinks,
+            ..test_config(&env)
+        });
+        let options = GameOptions {
+            ship_cells: 2,
+            ships_to_sink_p1: 2,
+            ships_to_sink_p2: 2,
+            ..Default::default()
+        };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        // Hits and sinks each come from the verifier, not the proof bytes
+        let expected_sunk = [0, 1, 1, 2];
+        for (x, sunk) in expected_sunk.into_iter().enumerate() {
+            let x = x as u32;
+            assert_eq!(client.get_state().phase, GamePhase::Playing);
+            client.fire_shot(&p1, &x, &0);
+            client.submit_response(&p2, &x, &0, &proof(&env, false));
+            assert_eq!(client.get_ships_sunk(), (0, sunk));
+        }
+
+        let state = client.get_state();
+        assert_eq!(state.hits_on_p2, 4);
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+    }
+
+    #[test]
+    fn test_hits_alone_do_not_win() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(sink_verifier::SinkVerifier, ());
+        client.initialize(&Config {
+            verifier: Some(verifier),
+            win_rule: WinRule::ProvenSinks,
+            ..test_config(&env)
+        });
+        let options = GameOptions { ship_cells: 2, ships_to_sink_p2: 1, ..Default::default() };
+        start_game_with(&env, &client, &p1, &p2, &options);
+
+        // One hit on each ship: two hits, but nothing sunk
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        client.fire_shot(&p1, &2, &0);
+        client.submit_response(&p2, &2, &0, &proof(&env, true));
+        assert_eq!(client.get_state().hits_on_p2, 2);
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+
+        client.fire_shot(&p1, &3, &0);
+        client.submit_response(&p2, &3, &0, &proof(&env, true));
+        assert_eq!(client.get_ships_sunk(), (0, 1));
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+    }
+}
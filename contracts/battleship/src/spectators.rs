@@ -0,0 +1,123 @@
+// Spectator registry. Doesn't touch game logic — it lets viewers register
+// against a game id (the session id) so front-ends can list who is watching
+// and follow the game's events. get_role tells a wallet which seat, if any,
+// it has in the current game.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::events::publish_for;
+use crate::{
+    assert_initialized, assert_not_reentrant, load_game, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, DataKey,
+};
+
+/// Where an address stands in the current game
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Role {
+    Player1,
+    Player2,
+    Spectator,
+    NotInGame,
+}
+
+fn load_spectators(env: &Env, game_id: u32) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Vec<Address>>(&DataKey::Spectators(game_id))
+        .unwrap_or(Vec::new(env))
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn add_spectator(env: Env, game_id: u32, spectator: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        spectator.require_auth();
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.session_id == game_id, "Unknown game");
+        assert!(
+            (!state.p1_joined || spectator != state.player1)
+                && (!state.p2_joined || spectator != state.player2),
+            "Players can't spectate"
+        );
+
+        let mut spectators = load_spectators(&env, game_id);
+        assert!(!spectators.contains(&spectator), "Already spectating");
+        spectators.push_back(spectator.clone());
+        env.storage().persistent().set(&DataKey::Spectators(game_id), &spectators);
+
+        publish_for(&env, game_id, "spectator_joined", spectator);
+    }
+
+    pub fn get_spectators(env: Env, game_id: u32) -> Vec<Address> {
+        load_spectators(&env, game_id)
+    }
+
+    pub fn get_role(env: Env, who: Address) -> Role {
+        let Some(state) = load_game(&env) else {
+            return Role::NotInGame;
+        };
+        if state.p1_joined && who == state.player1 {
+            Role::Player1
+        } else if state.p2_joined && who == state.player2 {
+            Role::Player2
+        } else if load_spectators(&env, state.session_id).contains(&who) {
+            Role::Spectator
+        } else {
+            Role::NotInGame
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Role;
+    use crate::tests::setup;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, Address};
+
+    #[test]
+    fn test_add_and_list_spectators() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        let game_id = client.get_state().session_id;
+
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        client.add_spectator(&game_id, &alice);
+        client.add_spectator(&game_id, &bob);
+
+        assert_eq!(client.get_spectators(&game_id), vec![&env, alice.clone(), bob]);
+        assert!(client.try_add_spectator(&game_id, &alice).is_err());
+        assert_eq!(client.get_spectators(&(game_id + 1)).len(), 0);
+    }
+
+    #[test]
+    fn test_get_role() {
+        let (env, p1, p2, client) = setup();
+        assert_eq!(client.get_role(&p1), Role::NotInGame);
+
+        client.join_game(&p1);
+        assert_eq!(client.get_role(&p1), Role::Player1);
+        assert_eq!(client.get_role(&p2), Role::NotInGame);
+
+        client.join_game(&p2);
+        let alice = Address::generate(&env);
+        client.add_spectator(&client.get_state().session_id, &alice);
+        assert_eq!(client.get_role(&p2), Role::Player2);
+        assert_eq!(client.get_role(&alice), Role::Spectator);
+        assert_eq!(client.get_role(&Address::generate(&env)), Role::NotInGame);
+    }
+
+    #[test]
+    #[should_panic(expected = "Players can't spectate")]
+    fn test_player_cannot_spectate() {
+        let (_env, p1, _p2, client) = setup();
+        client.join_game(&p1);
+        client.add_spectator(&client.get_state().session_id, &p1);
+    }
+}
@@ -0,0 +1,105 @@
+// Replacing a no-show before play starts. In Commit phase the admin may swap
+// either seat to a new address; the seat's board commitment is wiped so the
+// substitute commits their own, and in a wagered game the substitute pays
+// the stake while the player they replace gets theirs back.
+
+use soroban_sdk::{contractimpl, Address, BytesN, Env, Vec};
+
+use crate::events::publish;
+use crate::{
+    assert_initialized, assert_not_reentrant, is_blocked, load_config, load_game, save_game,
+    stake_token, track_replaced, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, GamePhase,
+};
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn substitute_player(env: Env, admin: Address, old: Address, new: Address) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        let config = load_config(&env).expect("Not initialized");
+        assert!(admin == config.admin, "Not admin");
+        admin.require_auth();
+        new.require_auth();
+
+        let mut state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::Commit, "Not in commit phase");
+        assert!(!is_blocked(&env, &new), "ADDRESS_BLOCKED");
+        assert!(new != state.player1 && new != state.player2, "Already in the game");
+
+        let empty: Vec<BytesN<32>> = Vec::new(&env);
+        if old == state.player1 {
+            state.player1 = new.clone();
+            state.board_hash_p1 = empty;
+            state.p1_committed = false;
+        } else if old == state.player2 {
+            state.player2 = new.clone();
+            state.board_hash_p2 = empty;
+            state.p2_committed = false;
+        } else {
+            panic!("Not a player");
+        }
+        // Nobody has fired yet; keep the other seat's bookkeeping in step
+        state.pending_shooter = state.player1.clone();
+        state.turn = state.player1.clone();
+        state.winner = state.player1.clone();
+
+        track_replaced(&env, &old, &new, state.session_id);
+        save_game(&env, &state);
+
+        if state.stake > 0 {
+            let token = stake_token(&env);
+            let contract = env.current_contract_address();
+            token.transfer(&new, &contract, &state.stake);
+            token.transfer(&contract, &old, &state.stake);
+        }
+        publish(&env, &state, "player_substituted", (old, new));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, setup_staking, setup_uninitialized, test_config};
+    use crate::{GameOptions, GamePhase};
+    use soroban_sdk::{testutils::Address as _, token, Address, Vec};
+
+    #[test]
+    fn test_substitute_must_recommit() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let token = token::Client::new(&env, &setup_staking(&env, &client, &[&p1, &p2]));
+        let admin = client.get_config().unwrap().admin;
+        let sub = Address::generate(&env);
+        token::StellarAssetClient::new(&env, &token.address).mint(&sub, &1000);
+
+        client.create_game(&p1, &GameOptions { stake: 100, ..Default::default() });
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+
+        client.substitute_player(&admin, &p1, &sub);
+        let state = client.get_state();
+        assert_eq!(state.player1, sub);
+        assert!(!state.p1_committed);
+        assert_eq!(state.board_hash_p1, Vec::new(&env));
+        assert_eq!((token.balance(&p1), token.balance(&sub)), (1000, 900));
+        assert!(client.try_commit_board(&p1, &hash(&env, 1)).is_err());
+
+        // The old commitment doesn't carry over
+        client.commit_board(&p2, &hash(&env, 2));
+        assert_eq!(client.get_state().phase, GamePhase::Commit);
+        client.commit_board(&sub, &hash(&env, 3));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.turn, sub);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not admin")]
+    fn test_substitute_needs_admin() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&test_config(&env));
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.substitute_player(&p1, &p2, &Address::generate(&env));
+    }
+}
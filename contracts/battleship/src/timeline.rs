@@ -0,0 +1,87 @@
+// Phase-transition timestamps for the current game, for analytics. Kept out
+// of GameState (which is at the contracttype field limit) under
+// DataKey::Timeline and cleared with the game on reset. 0 = not reached.
+// playing_started_sequence is the ledger, not the time, Playing began in.
+
+use soroban_sdk::{contractimpl, contracttype, Env};
+
+use crate::{BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey};
+
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameTimeline {
+    pub commit_started_at: u64,
+    pub created_at: u64,
+    pub finished_at: u64,
+    pub playing_started_at: u64,
+    pub playing_started_sequence: u32,
+}
+
+pub(crate) fn load_timeline(env: &Env) -> GameTimeline {
+    env.storage()
+        .instance()
+        .get::<DataKey, GameTimeline>(&DataKey::Timeline)
+        .unwrap_or_default()
+}
+
+// Records the current ledger time in the field `milestone` picks
+pub(crate) fn stamp(env: &Env, milestone: impl FnOnce(&mut GameTimeline) -> &mut u64) {
+    let mut timeline = load_timeline(env);
+    *milestone(&mut timeline) = env.ledger().timestamp();
+    env.storage().instance().set(&DataKey::Timeline, &timeline);
+}
+
+pub(crate) fn stamp_playing_sequence(env: &Env) {
+    let mut timeline = load_timeline(env);
+    timeline.playing_started_sequence = env.ledger().sequence();
+    env.storage().instance().set(&DataKey::Timeline, &timeline);
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    pub fn get_timeline(env: Env) -> GameTimeline {
+        load_timeline(&env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameTimeline;
+    use crate::tests::{hash, setup};
+    use soroban_sdk::testutils::Ledger;
+
+    #[test]
+    fn test_timeline() {
+        let (env, p1, p2, client) = setup();
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        client.join_game(&p1);
+
+        env.ledger().with_mut(|l| l.timestamp = 1_010);
+        client.join_game(&p2);
+
+        env.ledger().with_mut(|l| {
+            l.timestamp = 1_030;
+            l.sequence_number = 42;
+        });
+        client.commit_board(&p1, &hash(&env, 1));
+        client.commit_board(&p2, &hash(&env, 2));
+
+        env.ledger().with_mut(|l| l.timestamp = 1_100);
+        client.surrender(&p1);
+
+        assert_eq!(
+            client.get_timeline(),
+            GameTimeline {
+                commit_started_at: 1_010,
+                created_at: 1_000,
+                finished_at: 1_100,
+                playing_started_at: 1_030,
+                playing_started_sequence: 42,
+            }
+        );
+
+        client.reset_game(&p1);
+        assert_eq!(client.get_timeline(), GameTimeline::default());
+    }
+}
@@ -0,0 +1,275 @@
+// Single-elimination brackets layered on the one-game-at-a-time contract.
+// Bracket matches are played as ordinary games. Once both players have
+// joined, and before play starts, the tournament's creator schedules the game
+// for its bracket slot with schedule_bracket_match, which records it under
+// DataKey::BracketGame(session_id) as (tournament id, round, match index).
+// Once that game is Finished, anyone can call advance_tournament with its
+// session id to record its winner from the stored result, even after the
+// lobby has been reset or reused; no other game between the same two
+// players counts.
+
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Vec};
+
+use crate::recent_games::load_result;
+use crate::{
+    assert_initialized, assert_not_reentrant, load_game, BattleshipContract, BattleshipContractArgs,
+    BattleshipContractClient, DataKey, GamePhase, GameState,
+};
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BracketMatch {
+    pub player1: Address,
+    pub player2: Address,
+    pub winner: Option<Address>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    pub champion: Option<Address>,
+    pub creator: Address,
+    pub matches: Vec<BracketMatch>,
+    pub round: u32,
+}
+
+fn pair_round(env: &Env, players: &Vec<Address>) -> Vec<BracketMatch> {
+    let mut matches = Vec::new(env);
+    let mut i = 0;
+    while i < players.len() {
+        matches.push_back(BracketMatch {
+            player1: players.get_unchecked(i),
+            player2: players.get_unchecked(i + 1),
+            winner: None,
+        });
+        i += 2;
+    }
+    matches
+}
+
+fn load_tournament(env: &Env, tournament_id: u32) -> Tournament {
+    env.storage()
+        .persistent()
+        .get::<DataKey, Tournament>(&DataKey::Tournament(tournament_id))
+        .expect("No tournament")
+}
+
+// Index of the undecided match between the game's two players
+fn open_match(tournament: &Tournament, state: &GameState) -> Option<u32> {
+    let index = tournament.matches.iter().position(|m| {
+        m.winner.is_none()
+            && ((m.player1 == state.player1 && m.player2 == state.player2)
+                || (m.player1 == state.player2 && m.player2 == state.player1))
+    });
+    index.map(|index| index as u32)
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // Seeds round 1 in the given order: players[0] vs players[1], [2] vs [3], ...
+    pub fn create_tournament(env: Env, creator: Address, players: Vec<Address>) -> u32 {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        creator.require_auth();
+        let count = players.len();
+        assert!(count >= 2 && count.is_power_of_two(), "Player count must be a power of two");
+        for i in 0..count {
+            for j in (i + 1)..count {
+                assert!(players.get_unchecked(i) != players.get_unchecked(j), "Duplicate player");
+            }
+        }
+
+        let id = env.storage()
+            .persistent()
+            .get::<DataKey, u32>(&DataKey::TournamentCount)
+            .unwrap_or(0);
+
+        let tournament = Tournament {
+            champion: None,
+            creator,
+            matches: pair_round(&env, &players),
+            round: 1,
+        };
+        env.storage().persistent().set(&DataKey::Tournament(id), &tournament);
+        env.storage().persistent().set(&DataKey::TournamentCount, &(id + 1));
+        id
+    }
+
+    // Books the current game, still in Commit, as its players' match in the
+    // current round. Only the tournament's creator may.
+    pub fn schedule_bracket_match(env: Env, tournament_id: u32) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        let tournament = load_tournament(&env, tournament_id);
+        tournament.creator.require_auth();
+        assert!(tournament.champion.is_none(), "Tournament over");
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.phase == GamePhase::Commit, "Not in commit phase");
+        let index = open_match(&tournament, &state).expect("Game is not an open bracket match");
+        let key = DataKey::BracketGame(state.session_id);
+        assert!(!env.storage().persistent().has(&key), "Already scheduled");
+        env.storage().persistent().set(&key, &(tournament_id, tournament.round, index));
+    }
+
+    // Records the winner of game `session_id` in the bracket slot it was
+    // scheduled for and, once every match of the round is decided, seeds the
+    // next round.
+    pub fn advance_tournament(env: Env, tournament_id: u32, session_id: u32) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        let mut tournament = load_tournament(&env, tournament_id);
+        assert!(tournament.champion.is_none(), "Tournament over");
+
+        let (scheduled_for, round, index) = env.storage()
+            .persistent()
+            .get::<DataKey, (u32, u32, u32)>(&DataKey::BracketGame(session_id))
+            .expect("Game not scheduled");
+        assert!(
+            scheduled_for == tournament_id && round == tournament.round,
+            "Game not scheduled for this match"
+        );
+        let mut bracket_match = tournament.matches.get_unchecked(index);
+        assert!(bracket_match.winner.is_none(), "Match already decided");
+
+        // Recorded as the game finished, and amended by disputes and reveal forfeits
+        let result = load_result(&env, session_id).expect("Game not finished");
+        bracket_match.winner = Some(result.winner);
+        tournament.matches.set(index, bracket_match);
+
+        if tournament.matches.iter().all(|m| m.winner.is_some()) {
+            let mut winners = Vec::new(&env);
+            for m in tournament.matches.iter() {
+                winners.push_back(m.winner.unwrap());
+            }
+            if winners.len() == 1 {
+                tournament.champion = winners.first();
+            } else {
+                tournament.matches = pair_round(&env, &winners);
+                tournament.round += 1;
+            }
+        }
+
+        env.storage().persistent().set(&DataKey::Tournament(tournament_id), &tournament);
+    }
+
+    pub fn get_tournament(env: Env, tournament_id: u32) -> Option<Tournament> {
+        env.storage()
+            .persistent()
+            .get::<DataKey, Tournament>(&DataKey::Tournament(tournament_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{hash, proof, setup};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    // `winner` joins first, the game is booked for tournament `id` unless
+    // that's None, and `winner` sinks all three of `loser`'s ships. Returns
+    // the game's session id.
+    fn play(
+        env: &Env,
+        client: &BattleshipContractClient,
+        id: Option<u32>,
+        winner: &Address,
+        loser: &Address,
+    ) -> u32 {
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(winner);
+        client.join_game(loser);
+        if let Some(id) = id {
+            client.schedule_bracket_match(&id);
+        }
+        client.commit_board(winner, &hash(env, 1));
+        client.commit_board(loser, &hash(env, 2));
+        for coord in [(0u32, 0u32), (1, 1), (2, 2)] {
+            client.fire_shot(winner, &coord.0, &coord.1);
+            client.submit_response(loser, &coord.0, &coord.1, &proof(env, true));
+        }
+        client.get_state().session_id
+    }
+
+    #[test]
+    fn test_four_player_bracket() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        let p4 = Address::generate(&env);
+
+        let players = Vec::from_array(&env, [p1.clone(), p2.clone(), p3.clone(), p4.clone()]);
+        let id = client.create_tournament(&p1, &players);
+        assert_eq!(env.auths()[0].0, p1);
+
+        let game = play(&env, &client, Some(id), &p1, &p2);
+        client.advance_tournament(&id, &game);
+        client.reset_game(&p1);
+
+        // Recorded after the lobby has moved on
+        let game = play(&env, &client, Some(id), &p4, &p3);
+        client.reset_game(&p4);
+        env.ledger().with_mut(|l| l.sequence_number += 1);
+        client.join_game(&p2);
+        client.advance_tournament(&id, &game);
+        assert!(client.try_advance_tournament(&id, &game).is_err());
+        client.reset_game(&p2);
+
+        let tournament = client.get_tournament(&id).unwrap();
+        assert_eq!(tournament.round, 2);
+        assert_eq!(tournament.matches.len(), 1);
+        assert_eq!(tournament.matches.get_unchecked(0).player1, p1);
+        assert_eq!(tournament.matches.get_unchecked(0).player2, p4);
+
+        let game = play(&env, &client, Some(id), &p4, &p1);
+        client.advance_tournament(&id, &game);
+
+        let tournament = client.get_tournament(&id).unwrap();
+        assert_eq!(tournament.champion, Some(p4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Game is not an open bracket match")]
+    fn test_advance_with_unrelated_game() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        let p4 = Address::generate(&env);
+
+        let players = Vec::from_array(&env, [p1.clone(), p2.clone(), p3.clone(), p4.clone()]);
+        let id = client.create_tournament(&p1, &players);
+
+        let game = play(&env, &client, Some(id), &p1, &p3);
+        client.advance_tournament(&id, &game);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game not scheduled")]
+    fn test_advance_with_unscheduled_game() {
+        let (env, p1, p2, client) = setup();
+        let id = client.create_tournament(&p1, &Vec::from_array(&env, [p1.clone(), p2.clone()]));
+
+        // The right players, but a game nobody booked for the bracket
+        let game = play(&env, &client, None, &p2, &p1);
+        client.advance_tournament(&id, &game);
+    }
+
+    #[test]
+    #[should_panic(expected = "Game not scheduled for this match")]
+    fn test_advance_other_tournament() {
+        let (env, p1, p2, client) = setup();
+        let players = Vec::from_array(&env, [p1.clone(), p2.clone()]);
+        let id = client.create_tournament(&p1, &players);
+        let other = client.create_tournament(&p1, &players);
+
+        let game = play(&env, &client, Some(id), &p2, &p1);
+        client.advance_tournament(&other, &game);
+    }
+
+    #[test]
+    #[should_panic(expected = "Player count must be a power of two")]
+    fn test_bracket_needs_power_of_two() {
+        let (env, p1, p2, client) = setup();
+        let p3 = Address::generate(&env);
+        client.create_tournament(&p1, &Vec::from_array(&env, [p1.clone(), p2, p3]));
+    }
+}
@@ -0,0 +1,139 @@
+// Transparent games, GameMode::Transparent, for casual or test play where
+// privacy isn't wanted. Players commit their board in plaintext with
+// commit_plain_board instead of a hash, and submit_response answers shots
+// straight from the stored board, so the proof argument is ignored and
+// nothing is verified. The board is kept under
+// DataKey::PlainBoard(session_id, player); the committed board_hash is its
+// sha256, so the usual views still see a commitment.
+
+use soroban_sdk::{contractimpl, Address, Bytes, Env, Vec};
+
+use crate::{
+    assert_initialized, assert_not_reentrant, commit, load_game, BattleshipContract,
+    BattleshipContractArgs, BattleshipContractClient, DataKey, GameMode, GameState, ALL_CELLS,
+};
+
+// The player's plaintext board; None outside transparent mode
+pub(crate) fn plain_board(env: &Env, state: &GameState, player: &Address) -> Option<u32> {
+    if state.mode != GameMode::Transparent {
+        return None;
+    }
+    env.storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::PlainBoard(state.session_id, player.clone()))
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    /// commit_board for transparent games: `board` is the fleet itself, one
+    /// bit per cell at (y * BOARD_SIZE + x)
+    pub fn commit_plain_board(env: Env, player: Address, board: u32) {
+        assert_not_reentrant(&env);
+        assert_initialized(&env);
+        player.require_auth();
+
+        let state = load_game(&env).expect("No game");
+        assert!(state.mode == GameMode::Transparent, "Not a transparent game");
+        let fleet = if player == state.player1 {
+            state.ships_to_sink_p1
+        } else {
+            state.ships_to_sink_p2
+        };
+        assert!(board & !ALL_CELLS == 0, "Board out of range");
+        assert!(board.count_ones() == fleet * state.ship_cells, "Wrong fleet size");
+
+        let key = DataKey::PlainBoard(state.session_id, player.clone());
+        env.storage().persistent().set(&key, &board);
+        let digest = env.crypto().sha256(&Bytes::from_array(&env, &board.to_be_bytes()));
+        commit(&env, player, Vec::from_array(&env, [digest.into()]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{hash, salt, setup, setup_uninitialized, test_config};
+    use crate::{Config, GameMode, GameOptions, GamePhase};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{Address, Bytes};
+
+    fn transparent() -> GameOptions {
+        GameOptions {
+            mode: GameMode::Transparent,
+            ships_to_sink_p1: 2,
+            ships_to_sink_p2: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_transparent_game() {
+        let (env, p1, p2, client) = setup();
+        client.create_game(&p1, &transparent());
+        client.join_game(&p2);
+        // Ships at (0, 0) and (1, 0) for p1, (4, 4) and (3, 4) for p2
+        client.commit_plain_board(&p1, &0b11);
+        client.commit_plain_board(&p2, &(0b11 << 23));
+        assert_eq!(client.get_state().phase, GamePhase::Playing);
+
+        // Answers come from the boards, whatever the response carries
+        let no_proof = Bytes::new(&env);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &no_proof);
+        client.fire_shot(&p1, &4, &4);
+        client.submit_response(&p2, &4, &4, &no_proof);
+        assert_eq!(client.get_state().hits_on_p2, 1);
+
+        client.fire_shot(&p1, &3, &4);
+        client.submit_response(&p2, &3, &4, &no_proof);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+        assert_eq!(state.hit_mask_p2, 0b11 << 23);
+    }
+
+    #[test]
+    fn test_transparent_reveal_with_hasher() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let hasher = Address::generate(&env);
+        client.initialize(&Config { hasher: Some(hasher), ..test_config(&env) });
+        client.create_game(&p1, &transparent());
+        client.join_game(&p2);
+        client.commit_plain_board(&p1, &0b11);
+        client.commit_plain_board(&p2, &(0b11 << 23));
+        let no_proof = Bytes::new(&env);
+        for (x, y) in [(4u32, 4u32), (3, 4)] {
+            client.fire_shot(&p1, &x, &y);
+            client.submit_response(&p2, &x, &y, &no_proof);
+        }
+        assert_eq!(client.get_state().phase, GamePhase::Finished);
+
+        // Some other board that fits every answer
+        assert!(client.try_reveal_board(&p1, &0b110, &salt(&env)).is_err());
+        client.reveal_board(&p1, &0b11, &salt(&env));
+        client.reveal_board(&p2, &(0b11 << 23), &salt(&env));
+        let reveals = client.get_reveals();
+        assert!(reveals.revealed_p1 && reveals.revealed_p2);
+    }
+
+    #[test]
+    fn test_transparent_board_checked() {
+        let (env, p1, p2, client) = setup();
+        client.create_game(&p1, &transparent());
+        client.join_game(&p2);
+
+        assert!(client.try_commit_plain_board(&p1, &0b111).is_err());
+        assert!(client.try_commit_plain_board(&p1, &(1 << 25 | 1)).is_err());
+        // Hash commitments aren't answerable without proofs
+        assert!(client.try_commit_board(&p1, &hash(&env, 1)).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Not a transparent game")]
+    fn test_plain_board_needs_transparent_mode() {
+        let (_env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_plain_board(&p1, &1);
+    }
+}
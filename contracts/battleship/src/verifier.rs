@@ -0,0 +1,591 @@
+// ZK proof verification for submit_response. When Config.verifier is set the
+// proof goes to an UltraHonk verifier contract together with the circuit's
+// public inputs, each encoded as a 32-byte big-endian field element in the
+// order the circuit declares them: board_hash (one element per commitment
+// field), shot_x, shot_y. The circuit outputs is_hit, and the verifier hands
+// that output back for a valid proof, so the answer is never the defender's
+// word.
+// Without a verifier only the length sanity check applies, and the answer is
+// read from the proof's trailing output field unchecked.
+//
+// With Config.proof_version set, every proof starts with one version byte
+// naming the circuit it came from. Proofs from any other circuit are refused
+// before reaching the verifier, which only sees the bytes after the prefix.
+//
+// Under WinRule::ProvenSinks and WinRule::Points the sink circuit is used
+// instead: its public inputs add the defender's hit mask so far after
+// shot_y, and it outputs is_hit then sunk — the value of the ship the hit
+// completed, or 0 if it didn't complete one. ProvenSinks counts any sunk
+// ship as one; Points adds up the values. The verifier's verify_sink_proof
+// hands back both outputs. Unverified, they are read from the proof's last
+// two fields.
+//
+// The sha256 of every accepted proof, exactly as submitted, is kept per move
+// and published as "move_proven", so spectators can fetch the proofs
+// off-chain and check each one against the game.
+
+use soroban_sdk::{contractclient, contractimpl, Address, Bytes, BytesN, Env, Vec};
+
+use crate::events::publish;
+use crate::fleet::{self, fleet_size};
+use crate::{
+    bump, forfeit_game, load_config, load_game, next_turn_deadline, other_player, win_rule,
+    BattleshipContract, BattleshipContractArgs, BattleshipContractClient, DataKey, GamePhase,
+    GameState, WinRule,
+};
+
+#[allow(dead_code)]
+#[contractclient(name = "VerifierClient")]
+pub trait ProofVerifier {
+    // The circuit's is_hit output, or None if the proof doesn't verify
+    fn verify_proof(env: Env, public_inputs: Bytes, proof: Bytes) -> Option<bool>;
+
+    // The sink circuit's (is_hit, sunk) outputs, or None if the proof doesn't verify
+    fn verify_sink_proof(env: Env, public_inputs: Bytes, proof: Bytes) -> Option<(bool, u32)>;
+}
+
+fn field(env: &Env, value: u32) -> Bytes {
+    let mut bytes = [0u8; 32];
+    bytes[28..].copy_from_slice(&value.to_be_bytes());
+    Bytes::from_array(env, &bytes)
+}
+
+pub(crate) fn public_inputs(
+    env: &Env,
+    board_hash: &Vec<BytesN<32>>,
+    x: u32,
+    y: u32,
+) -> Bytes {
+    let mut inputs = Bytes::new(env);
+    for element in board_hash.iter() {
+        inputs.append(&element.into());
+    }
+    inputs.append(&field(env, x));
+    inputs.append(&field(env, y));
+    inputs
+}
+
+fn sink_public_inputs(
+    env: &Env,
+    board_hash: &Vec<BytesN<32>>,
+    x: u32,
+    y: u32,
+    hit_mask: u32,
+) -> Bytes {
+    let mut inputs = public_inputs(env, board_hash, x, y);
+    inputs.append(&field(env, hit_mask));
+    inputs
+}
+
+// Checks and drops the version prefix
+fn strip_version(proof: &Bytes, version: u32) -> Bytes {
+    assert!(proof.first().map(u32::from) == Some(version), "PROOF_VERSION_MISMATCH");
+    proof.slice(1..)
+}
+
+fn proves_sinks(env: &Env) -> bool {
+    matches!(win_rule(env), WinRule::ProvenSinks | WinRule::Points(_))
+}
+
+// The last byte of the proof's final field, where the circuit's output sits;
+// the sink circuit's sunk output is the field before it
+fn unverified_outcome(proof: &Bytes, sinks: bool) -> (bool, u32) {
+    let is_hit = proof.last() == Some(1);
+    if !sinks {
+        return (is_hit, 0);
+    }
+    let end = proof.len() - 32;
+    let sunk = (end - 4..end).fold(0, |acc, i| (acc << 8) | proof.get_unchecked(i) as u32);
+    (is_hit, sunk)
+}
+
+// (is_hit, sunk) from the configured verifier: Err if it couldn't be
+// reached, Ok(None) if it rejected the proof. The host reports any failed
+// call the same way, so a trap on a malformed proof looks like a missing
+// verifier; an empty probe proof, which the defender has no say in, tells
+// them apart. A verifier that answers the probe is up, and the defender's
+// proof counts as rejected.
+fn call_verifier(
+    env: &Env,
+    verifier: &Address,
+    state: &GameState,
+    defender: &Address,
+    x: u32,
+    y: u32,
+    proof: &Bytes,
+) -> Result<Option<(bool, u32)>, ()> {
+    let (board_hash, hit_mask) = if fleet_size(state).is_some() {
+        fleet::target(env, state, defender)
+    } else if *defender == state.player1 {
+        (state.board_hash_p1.clone(), state.hit_mask_p1)
+    } else {
+        (state.board_hash_p2.clone(), state.hit_mask_p2)
+    };
+    let client = VerifierClient::new(env, verifier);
+    if proves_sinks(env) {
+        let inputs = sink_public_inputs(env, &board_hash, x, y, hit_mask);
+        match client.try_verify_sink_proof(&inputs, proof) {
+            Ok(Ok(outcome)) => Ok(outcome),
+            _ if client.try_verify_sink_proof(&inputs, &Bytes::new(env)).is_ok() => Ok(None),
+            _ => Err(()),
+        }
+    } else {
+        let inputs = public_inputs(env, &board_hash, x, y);
+        match client.try_verify_proof(&inputs, proof) {
+            Ok(Ok(outcome)) => Ok(outcome.map(|is_hit| (is_hit, 0))),
+            _ if client.try_verify_proof(&inputs, &Bytes::new(env)).is_ok() => Ok(None),
+            _ => Err(()),
+        }
+    }
+}
+
+// Must run before submit_response touches any state — in particular before
+// a winning hit can finish the game. Returns the proven (is_hit, sunk) —
+// sunk is always 0 without the sink circuit, and only counts on a hit — or None when
+// a rejected proof was recorded as a strike, or the verifier was unreachable
+// and the game is now Paused, instead of reverting; the caller then saves
+// `state` and stops.
+pub(crate) fn verify_zk_proof(
+    env: &Env,
+    state: &mut GameState,
+    defender: &Address,
+    x: u32,
+    y: u32,
+    proof: &Bytes,
+) -> Option<(bool, u32)> {
+    let config = load_config(env);
+    let proof = &match config.as_ref().map(|config| config.proof_version) {
+        Some(version) if version > 0 => strip_version(proof, version),
+        _ => proof.clone(),
+    };
+    let sinks = proves_sinks(env);
+    assert!(proof.len() >= if sinks { 64 } else { 32 }, "Proof too short");
+
+    let Some(config) = config else {
+        count_verified(env, state);
+        publish(env, state, "zk_verified", proof.len());
+        return Some(unverified_outcome(proof, sinks));
+    };
+    let mut outcome = unverified_outcome(proof, sinks);
+    if let Some(verifier) = config.verifier {
+        let proven = match call_verifier(env, &verifier, state, defender, x, y, proof) {
+            Ok(proven) => proven,
+            Err(()) => {
+                assert!(config.pause_on_verifier_error, "Verifier unavailable");
+                state.phase = GamePhase::Paused;
+                // PAUSE_TIMEOUT counts from here
+                state.last_move_at = env.ledger().timestamp();
+                publish(env, state, "verifier_unavailable", defender.clone());
+                return None;
+            }
+        };
+        let Some(proven) = proven else {
+            assert!(config.max_invalid_proofs > 0, "Invalid proof");
+            record_invalid_proof(env, state, defender, config.max_invalid_proofs);
+            return None;
+        };
+        outcome = proven;
+    }
+
+    if state.phase == GamePhase::Paused {
+        // The outage wasn't anyone's move — restart the clock and deadline
+        state.phase = GamePhase::Playing;
+        state.last_move_at = env.ledger().timestamp();
+        state.turn_deadline = next_turn_deadline(env);
+    }
+
+    count_verified(env, state);
+    publish(env, state, "zk_verified", proof.len());
+    let (is_hit, sunk) = outcome;
+    Some((is_hit, if is_hit { sunk } else { 0 }))
+}
+
+fn proofs_verified(env: &Env, session_id: u32) -> u32 {
+    env.storage()
+        .persistent()
+        .get::<DataKey, u32>(&DataKey::ProofsVerified(session_id))
+        .unwrap_or(0)
+}
+
+fn count_verified(env: &Env, state: &GameState) {
+    let mut count = proofs_verified(env, state.session_id);
+    bump(&mut count);
+    env.storage().persistent().set(&DataKey::ProofsVerified(state.session_id), &count);
+}
+
+// `move_index` is the move number before the response is counted, so the
+// first exchange of a game is move 0
+pub(crate) fn record_proof_hash(env: &Env, state: &GameState, move_index: u32, proof: &Bytes) {
+    let hash: BytesN<32> = env.crypto().sha256(proof).into();
+    let key = DataKey::MoveProof(state.session_id, move_index);
+    env.storage().persistent().set(&key, &hash);
+    publish(env, state, "move_proven", (move_index, hash));
+}
+
+// One more strike for `defender`; reaching `limit` loses them the game.
+fn record_invalid_proof(env: &Env, state: &mut GameState, defender: &Address, limit: u32) {
+    let strikes = if *defender == state.player1 {
+        &mut state.invalid_proofs_p1
+    } else {
+        &mut state.invalid_proofs_p2
+    };
+    bump(strikes);
+    let strikes = *strikes;
+
+    publish(env, state, "proof_rejected", (defender.clone(), strikes));
+    if strikes >= limit {
+        forfeit_game(env, state, other_player(state, defender));
+    }
+}
+
+#[contractimpl]
+impl BattleshipContract {
+
+    // Responses whose proof was accepted in the current game
+    pub fn get_proofs_verified(env: Env) -> u32 {
+        let state = load_game(&env).expect("No game");
+        proofs_verified(&env, state.session_id)
+    }
+
+    // sha256 of the proof that answered move `move_index` of the current
+    // game; None for moves not answered by a proof
+    pub fn get_move_proof_hash(env: Env, move_index: u32) -> Option<BytesN<32>> {
+        let state = load_game(&env).expect("No game");
+        env.storage()
+            .persistent()
+            .get::<DataKey, BytesN<32>>(&DataKey::MoveProof(state.session_id, move_index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::public_inputs;
+    use crate::inactivity::PAUSE_TIMEOUT;
+    use crate::tests::{hash, proof, setup, setup_uninitialized, start_game, test_config};
+    use crate::{Config, GamePhase};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        Address, Bytes, BytesN, Env, Vec,
+    };
+
+    // Accepts any proof except ones starting with a zero byte, answering with
+    // the proof's last byte as the hit result, and remembers the public
+    // inputs it was given
+    mod mock_verifier {
+        use soroban_sdk::{contract, contractimpl, symbol_short, Bytes, Env};
+
+        #[contract]
+        pub struct MockVerifier;
+
+        #[contractimpl]
+        impl MockVerifier {
+            pub fn verify_proof(env: Env, public_inputs: Bytes, proof: Bytes) -> Option<bool> {
+                env.storage().instance().set(&symbol_short!("inputs"), &public_inputs);
+                let valid = public_inputs.len().is_multiple_of(32) && proof.get(0) != Some(0);
+                valid.then(|| proof.last() == Some(1))
+            }
+
+            pub fn last_inputs(env: Env) -> Bytes {
+                env.storage().instance().get(&symbol_short!("inputs")).unwrap()
+            }
+        }
+    }
+
+    // Traps on any proof it's given, as a verifier fed a malformed one
+    // might, but answers an empty one
+    mod trapping_verifier {
+        use soroban_sdk::{contract, contractimpl, Bytes, Env};
+
+        #[contract]
+        pub struct TrappingVerifier;
+
+        #[contractimpl]
+        impl TrappingVerifier {
+            pub fn verify_proof(_env: Env, _public_inputs: Bytes, proof: Bytes) -> Option<bool> {
+                assert!(proof.is_empty(), "malformed proof");
+                None
+            }
+        }
+    }
+
+    // Answers every proof with whatever outcome the test set, like a circuit
+    // reading the real board
+    mod fixed_verifier {
+        use soroban_sdk::{contract, contractimpl, symbol_short, Bytes, Env};
+
+        #[contract]
+        pub struct FixedVerifier;
+
+        #[contractimpl]
+        impl FixedVerifier {
+            pub fn set_outcome(env: Env, is_hit: bool) {
+                env.storage().instance().set(&symbol_short!("hit"), &is_hit);
+            }
+
+            pub fn verify_proof(env: Env, _public_inputs: Bytes, _proof: Bytes) -> Option<bool> {
+                env.storage().instance().get(&symbol_short!("hit"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_outcome_comes_from_verifier() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(fixed_verifier::FixedVerifier, ());
+        let oracle = fixed_verifier::FixedVerifierClient::new(&env, &verifier);
+        client.initialize(&Config { verifier: Some(verifier.clone()), ..test_config(&env) });
+
+        start_game(&env, &client, &p1, &p2);
+
+        // The defender's proof claims a miss; the verified output says hit
+        oracle.set_outcome(&true);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        let state = client.get_state();
+        assert_eq!(state.hits_on_p2, 1);
+        assert_eq!(state.hit_mask_p2, 1);
+
+        // And the other way round
+        oracle.set_outcome(&false);
+        client.fire_shot(&p1, &1, &1);
+        client.submit_response(&p2, &1, &1, &proof(&env, true));
+        assert_eq!(client.get_state().hits_on_p2, 1);
+    }
+
+    #[test]
+    fn test_fake_proof_cannot_win() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(mock_verifier::MockVerifier, ());
+        client.initialize(&Config { verifier: Some(verifier), ..test_config(&env) });
+
+        start_game(&env, &client, &p1, &p2);
+        for coord in [(0u32, 0u32), (1, 1)] {
+            client.fire_shot(&p1, &coord.0, &coord.1);
+            client.submit_response(&p2, &coord.0, &coord.1, &proof(&env, true));
+        }
+
+        // The game-ending hit is what a cheater would fake
+        client.fire_shot(&p1, &2, &2);
+        let fake = Bytes::from_slice(&env, &[0u8; 64]);
+        assert!(client.try_submit_response(&p2, &2, &2, &fake).is_err());
+
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.hits_on_p2, 2);
+        assert!(!state.has_winner);
+
+        client.submit_response(&p2, &2, &2, &proof(&env, true));
+        assert_eq!(client.get_state().winner, p1);
+    }
+
+    #[test]
+    fn test_proofs_verified_count() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+        assert_eq!(client.get_proofs_verified(), 0);
+
+        for x in 0..3u32 {
+            client.fire_shot(&p1, &x, &4);
+            client.submit_response(&p2, &x, &4, &proof(&env, false));
+            assert_eq!(client.get_proofs_verified(), x + 1);
+        }
+    }
+
+    #[test]
+    fn test_move_proof_hashes() {
+        let (env, p1, p2, client) = setup();
+        start_game(&env, &client, &p1, &p2);
+
+        let proofs = [proof(&env, false), proof(&env, true)];
+        for (x, proof) in proofs.iter().enumerate() {
+            client.fire_shot(&p1, &(x as u32), &0);
+            client.submit_response(&p2, &(x as u32), &0, proof);
+        }
+        for (i, proof) in proofs.iter().enumerate() {
+            let expected: BytesN<32> = env.crypto().sha256(proof).into();
+            assert_eq!(client.get_move_proof_hash(&(i as u32)), Some(expected));
+        }
+        assert_eq!(client.get_move_proof_hash(&2), None);
+    }
+
+    #[test]
+    fn test_multi_element_commitment() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(mock_verifier::MockVerifier, ());
+        client.initialize(&Config { verifier: Some(verifier.clone()), ..test_config(&env) });
+
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_board(&p1, &hash(&env, 1));
+        let commitment = Vec::from_array(&env, [hash(&env, 2), hash(&env, 3)]);
+        client.commit_board_elements(&p2, &commitment);
+        assert_eq!(client.get_state().board_hash_p2, commitment);
+
+        client.fire_shot(&p1, &1, &2);
+        client.submit_response(&p2, &1, &2, &proof(&env, false));
+
+        let inputs = mock_verifier::MockVerifierClient::new(&env, &verifier).last_inputs();
+        assert_eq!(inputs.len(), 4 * 32);
+        assert_eq!(inputs, public_inputs(&env, &commitment, 1, 2));
+    }
+
+    fn versioned(env: &Env, version: u8, is_hit: bool) -> Bytes {
+        let mut bytes = Bytes::from_array(env, &[version]);
+        bytes.append(&proof(env, is_hit));
+        bytes
+    }
+
+    #[test]
+    fn test_proof_version_matches() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(mock_verifier::MockVerifier, ());
+        client.initialize(&Config {
+            proof_version: 2,
+            verifier: Some(verifier),
+            ..test_config(&env)
+        });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &versioned(&env, 2, true));
+        assert_eq!(client.get_state().hits_on_p2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "PROOF_VERSION_MISMATCH")]
+    fn test_proof_version_mismatch() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { proof_version: 2, ..test_config(&env) });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &versioned(&env, 1, true));
+    }
+
+    #[test]
+    #[should_panic(expected = "Empty commitment")]
+    fn test_empty_commitment() {
+        let (env, p1, p2, client) = setup();
+        client.join_game(&p1);
+        client.join_game(&p2);
+        client.commit_board_elements(&p1, &Vec::new(&env));
+    }
+
+    #[test]
+    fn test_disqualified_after_invalid_proofs() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(mock_verifier::MockVerifier, ());
+        client.initialize(&Config {
+            verifier: Some(verifier),
+            max_invalid_proofs: 2,
+            ..test_config(&env)
+        });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+
+        // First strike is recorded; the shot is still waiting for an answer
+        let fake = Bytes::from_slice(&env, &[0u8; 64]);
+        client.submit_response(&p2, &0, &0, &fake);
+        let state = client.get_state();
+        assert_eq!(state.invalid_proofs_p2, 1);
+        assert_eq!(client.get_proofs_verified(), 0);
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(client.get_pending_defender(), Some(p2.clone()));
+
+        client.submit_response(&p2, &0, &0, &fake);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+        assert_eq!(client.get_forfeit_count(&p2), 1);
+    }
+
+    #[test]
+    fn test_missing_verifier_pauses_game() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = Address::generate(&env);
+        client.initialize(&Config {
+            verifier: Some(verifier.clone()),
+            pause_on_verifier_error: true,
+            turn_timeout: 300,
+            ..test_config(&env)
+        });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+
+        // No contract at the verifier address: the response doesn't revert
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Paused);
+        assert_eq!(state.hits_on_p2, 0);
+        assert_eq!(client.get_pending_defender(), Some(p2.clone()));
+
+        // Nobody can profit from the outage
+        env.ledger().with_mut(|l| l.timestamp += 1_000);
+        assert!(client.try_claim_timeout(&p1).is_err());
+
+        // Once the verifier is back, the same response goes through
+        env.register_at(&verifier, mock_verifier::MockVerifier, ());
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.hits_on_p2, 1);
+    }
+
+    #[test]
+    fn test_verifier_trap_is_a_rejection() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        let verifier = env.register(trapping_verifier::TrappingVerifier, ());
+        client.initialize(&Config {
+            verifier: Some(verifier),
+            pause_on_verifier_error: true,
+            max_invalid_proofs: 3,
+            ..test_config(&env)
+        });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+
+        // The defender can't stall the game with a proof the verifier chokes on
+        client.submit_response(&p2, &0, &0, &proof(&env, false));
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Playing);
+        assert_eq!(state.invalid_proofs_p2, 1);
+    }
+
+    #[test]
+    fn test_pause_times_out_into_claim() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config {
+            verifier: Some(Address::generate(&env)),
+            pause_on_verifier_error: true,
+            ..test_config(&env)
+        });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        env.ledger().with_mut(|l| l.timestamp = 1_000);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+        assert_eq!(client.get_state().phase, GamePhase::Paused);
+
+        env.ledger().with_mut(|l| l.timestamp += PAUSE_TIMEOUT - 1);
+        assert!(client.try_claim_inactivity(&p1).is_err());
+        env.ledger().with_mut(|l| l.timestamp += 1);
+        client.claim_inactivity(&p1);
+        let state = client.get_state();
+        assert_eq!(state.phase, GamePhase::Finished);
+        assert_eq!(state.winner, p1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Verifier unavailable")]
+    fn test_missing_verifier_reverts_by_default() {
+        let (env, p1, p2, client) = setup_uninitialized();
+        client.initialize(&Config { verifier: Some(Address::generate(&env)), ..test_config(&env) });
+
+        start_game(&env, &client, &p1, &p2);
+        client.fire_shot(&p1, &0, &0);
+        client.submit_response(&p2, &0, &0, &proof(&env, true));
+    }
+}